@@ -22,7 +22,7 @@ DEFAULT_METHOD = 3
 
 class Test(AcceleratedPerfTest):
     name = "collision"
-    instances = (({"method": 3}, "Simple"),)
+    instances = (({"method": 3}, "Simple"), ({"method": 1}, "Spatial Hash"))
 
     def __init__(self, session_dir: Path, method: int = DEFAULT_METHOD):
         super().__init__(
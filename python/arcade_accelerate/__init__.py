@@ -3,6 +3,7 @@ import sys
 from arcade_accelerate import arcade_accelerate
 from arcade_accelerate.module_patcher import (
     AutoPopulatingDictionary,
+    DeferredPatch,
     PatchingMetaPathFinder,
 )
 
@@ -16,6 +17,9 @@ def bootstrap():
     patch_geometry(patches)
     patch_hitboxes(patches)
     patch_spritelist_collision(patches)
+    patch_spatial_hash(patches)
+    patch_hitbox_algorithms(patches)
+    patch_paths(patches)
 
 
 def patch_hitboxes(patches):
@@ -23,6 +27,62 @@ def patch_hitboxes(patches):
     patches["arcade.hitbox.base"].RotatableHitBox = arcade_accelerate.RotatableHitBox
 
 
+def patch_hitbox_algorithms(patches):
+    patches["arcade.hitbox.simple"].SimpleHitBoxAlgorithm = DeferredPatch(
+        lambda module: _accelerate_hit_box_algorithm(
+            module.SimpleHitBoxAlgorithm,
+            arcade_accelerate.calculate_hit_box_points_simple,
+        )
+    )
+    patches["arcade.hitbox.bounding_box"].BoundingHitBoxAlgorithm = DeferredPatch(
+        lambda module: _accelerate_hit_box_algorithm(
+            module.BoundingHitBoxAlgorithm,
+            arcade_accelerate.calculate_hit_box_points_bounding,
+        )
+    )
+    patches["arcade.hitbox.pymunk"].PymunkHitBoxAlgorithm = DeferredPatch(
+        _accelerate_detailed_hit_box_algorithm
+    )
+
+
+def _accelerate_detailed_hit_box_algorithm(module):
+    """Trace detailed hit boxes in Rust instead of with pymunk's autogeometry.
+
+    This only replaces the algorithm, not the dependency: arcade imports
+    ``arcade.hitbox.pymunk`` on its own, and that module still imports pymunk. To
+    go without pymunk, call ``calculate_hit_box_points_detailed`` directly.
+    """
+    base = module.PymunkHitBoxAlgorithm
+
+    def calculate(self, image, detail=None, **kwargs):
+        if image.mode != "RGBA":
+            raise ValueError("Image mode is not RGBA. image.convert('RGBA') is needed.")
+        return tuple(
+            arcade_accelerate.calculate_hit_box_points_detailed(
+                image.tobytes(),
+                image.width,
+                image.height,
+                detail if detail is not None else getattr(self, "detail", None),
+            )
+        )
+
+    return type(base.__name__, (base,), {"calculate": calculate})
+
+
+def _accelerate_hit_box_algorithm(base, calculate_points):
+    """Subclass an arcade hit box algorithm to compute its points in Rust.
+
+    Subclassing keeps the class name, which arcade uses for its hit box cache keys.
+    """
+
+    def calculate(self, image, **kwargs):
+        if image.mode != "RGBA":
+            raise ValueError("Image mode is not RGBA. image.convert('RGBA') is needed.")
+        return tuple(calculate_points(image.tobytes(), image.width, image.height))
+
+    return type(base.__name__, (base,), {"calculate": calculate})
+
+
 def patch_spritelist_collision(patches):
     patches["arcade.sprite_list.collision"].check_for_collision_with_list = (
         arcade_accelerate.check_for_collision_with_list
@@ -32,6 +92,20 @@ def patch_spritelist_collision(patches):
     )
 
 
+def patch_spatial_hash(patches):
+    patches["arcade.sprite_list.spatial_hash"].SpatialHash = (
+        arcade_accelerate.SpatialHash
+    )
+
+
+def patch_paths(patches):
+    patches["arcade.paths"].has_line_of_sight = arcade_accelerate.has_line_of_sight
+    patches["arcade.paths"].AStarBarrierList = arcade_accelerate.AStarBarrierList
+    patches["arcade.paths"].astar_calculate_path = (
+        arcade_accelerate.astar_calculate_path
+    )
+
+
 def patch_math(patches):
     patches["arcade.math"].rotate_point = arcade_accelerate.rotate_point
     patches["arcade.math"].clamp = arcade_accelerate.clamp
@@ -6,6 +6,16 @@ from types import ModuleType, SimpleNamespace
 from typing import Sequence
 
 
+class DeferredPatch:
+    """A patch built from the original module after it has been executed.
+
+    Lets a patch subclass or wrap something the module itself defines.
+    """
+
+    def __init__(self, factory):
+        self.factory = factory
+
+
 class AutoPopulatingDictionary(dict):
     def __missing__(self, key):
         self[key] = item = SimpleNamespace()
@@ -50,4 +60,7 @@ class PatchingLoader(Loader):
 
     def exec_module(self, module: ModuleType) -> None:
         self._loader.exec_module(module)
-        module.__dict__.update(self._patches)
+        for name, value in self._patches.items():
+            if isinstance(value, DeferredPatch):
+                value = value.factory(module)
+            module.__dict__[name] = value
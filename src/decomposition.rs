@@ -0,0 +1,267 @@
+use pyo3::prelude::*;
+
+use crate::geometry::{cross, normalize_polygon_winding_native};
+
+/// Whether `p` is inside or on the edges of the counter-clockwise triangle `a`, `b`, `c`.
+fn is_point_in_triangle(p: (f32, f32), a: (f32, f32), b: (f32, f32), c: (f32, f32)) -> bool {
+    cross(a, b, p) >= 0.0 && cross(b, c, p) >= 0.0 && cross(c, a, p) >= 0.0
+}
+
+/// Whether the vertex `cur` turns left (or goes straight) coming from `prev` towards `next`.
+///
+/// Allows a small relative error so float noise along straight edges does not
+/// reject otherwise convex shapes.
+fn is_convex_turn(prev: (f32, f32), cur: (f32, f32), next: (f32, f32)) -> bool {
+    let length_in = (cur.0 - prev.0).hypot(cur.1 - prev.1);
+    let length_out = (next.0 - cur.0).hypot(next.1 - cur.1);
+    cross(prev, cur, next) >= -1.0e-6 * length_in * length_out
+}
+
+fn is_index_polygon_convex(points: &[(f32, f32)], polygon: &[usize]) -> bool {
+    let n = polygon.len();
+    (0..n).all(|i| {
+        is_convex_turn(
+            points[polygon[(i + n - 1) % n]],
+            points[polygon[i]],
+            points[polygon[(i + 1) % n]],
+        )
+    })
+}
+
+/// Drop repeated points (including a closing point equal to the first) and make the
+/// winding counter-clockwise.
+fn normalize_outline(polygon: &[(f32, f32)]) -> Vec<(f32, f32)> {
+    let mut points: Vec<(f32, f32)> = Vec::with_capacity(polygon.len());
+    for point in polygon {
+        if points.last() != Some(point) {
+            points.push(*point);
+        }
+    }
+    while points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+    normalize_polygon_winding_native(&mut points, false);
+    points
+}
+
+/// Ear clipping triangulation of a counter-clockwise simple polygon, as index triples.
+///
+/// Collinear vertices are dropped rather than producing flat triangles. If no ear can
+/// be found, which only happens for self-intersecting input, the sharpest convex
+/// vertex is clipped anyway so the result is always finite.
+fn triangulate(points: &[(f32, f32)]) -> Vec<[usize; 3]> {
+    let mut remaining: Vec<usize> = (0..points.len()).collect();
+    let mut triangles: Vec<[usize; 3]> = Vec::with_capacity(points.len());
+
+    while remaining.len() > 3 {
+        let m = remaining.len();
+        let corner = |i: usize| {
+            (
+                remaining[(i + m - 1) % m],
+                remaining[i],
+                remaining[(i + 1) % m],
+            )
+        };
+
+        let mut clipped: Option<usize> = None;
+        for i in 0..m {
+            let (prev, cur, next) = corner(i);
+            let turn = cross(points[prev], points[cur], points[next]);
+            if turn == 0.0 {
+                clipped = Some(i);
+                break;
+            }
+            if turn < 0.0 {
+                continue;
+            }
+            let is_ear = remaining.iter().all(|&k| {
+                let p = points[k];
+                p == points[prev]
+                    || p == points[cur]
+                    || p == points[next]
+                    || !is_point_in_triangle(p, points[prev], points[cur], points[next])
+            });
+            if is_ear {
+                triangles.push([prev, cur, next]);
+                clipped = Some(i);
+                break;
+            }
+        }
+
+        let index = clipped.unwrap_or_else(|| {
+            let sharpest = (0..m)
+                .max_by(|a, b| {
+                    let (pa, ca, na) = corner(*a);
+                    let (pb, cb, nb) = corner(*b);
+                    cross(points[pa], points[ca], points[na])
+                        .total_cmp(&cross(points[pb], points[cb], points[nb]))
+                })
+                .unwrap_or(0);
+            let (prev, cur, next) = corner(sharpest);
+            triangles.push([prev, cur, next]);
+            sharpest
+        });
+        remaining.remove(index);
+    }
+
+    if remaining.len() == 3
+        && cross(
+            points[remaining[0]],
+            points[remaining[1]],
+            points[remaining[2]],
+        ) != 0.0
+    {
+        triangles.push([remaining[0], remaining[1], remaining[2]]);
+    }
+    triangles
+}
+
+/// Join two counter-clockwise index polygons across a shared edge, if the result is convex.
+fn try_merge(points: &[(f32, f32)], a: &[usize], b: &[usize]) -> Option<Vec<usize>> {
+    let n = a.len();
+    let m = b.len();
+    for i in 0..n {
+        let (start, end) = (a[i], a[(i + 1) % n]);
+        // The shared edge runs the opposite way in the other polygon
+        let Some(j) = (0..m).find(|&j| b[j] == end && b[(j + 1) % m] == start) else {
+            continue;
+        };
+        // Walk `a` from `end` round to `start`, then `b` from after `start` to before `end`
+        let mut merged: Vec<usize> = Vec::with_capacity(n + m - 2);
+        merged.extend((0..n).map(|k| a[(i + 1 + k) % n]));
+        merged.extend((0..m - 2).map(|k| b[(j + 2 + k) % m]));
+        if is_index_polygon_convex(points, &merged) {
+            return Some(merged);
+        }
+    }
+    None
+}
+
+/// Split a simple polygon into convex pieces.
+///
+/// The polygon is triangulated by ear clipping and the triangles are then greedily
+/// merged across their shared diagonals while the result stays convex
+/// (Hertel-Mehlhorn), which uses at most four times the optimal number of pieces.
+/// Pieces are returned counter-clockwise. Convex input comes back as a single piece
+/// and input with fewer than three distinct points gives no pieces.
+pub fn decompose_polygon_native(polygon: &[(f32, f32)]) -> Vec<Vec<(f32, f32)>> {
+    let points = normalize_outline(polygon);
+    if points.len() < 3 {
+        return vec![];
+    }
+    let all: Vec<usize> = (0..points.len()).collect();
+    if is_index_polygon_convex(&points, &all) {
+        return vec![points];
+    }
+
+    let mut parts: Vec<Vec<usize>> = triangulate(&points)
+        .iter()
+        .map(|triangle| triangle.to_vec())
+        .collect();
+
+    let mut merged_any = true;
+    while merged_any {
+        merged_any = false;
+        'search: for i in 0..parts.len() {
+            for j in (i + 1)..parts.len() {
+                if let Some(merged) = try_merge(&points, &parts[i], &parts[j]) {
+                    parts[i] = merged;
+                    parts.swap_remove(j);
+                    merged_any = true;
+                    break 'search;
+                }
+            }
+        }
+    }
+
+    parts
+        .iter()
+        .map(|part| part.iter().map(|&index| points[index]).collect())
+        .collect()
+}
+
+#[pyfunction]
+pub fn decompose_polygon(polygon: Vec<(f32, f32)>) -> Vec<Vec<(f32, f32)>> {
+    decompose_polygon_native(&polygon)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::get_polygon_signed_area_native;
+
+    fn total_area(parts: &[Vec<(f32, f32)>]) -> f32 {
+        parts
+            .iter()
+            .map(|part| get_polygon_signed_area_native(part))
+            .sum()
+    }
+
+    fn assert_convex(parts: &[Vec<(f32, f32)>]) {
+        for part in parts {
+            let indices: Vec<usize> = (0..part.len()).collect();
+            assert!(is_index_polygon_convex(part, &indices), "{:?}", part);
+        }
+    }
+
+    #[test]
+    fn test_decompose_convex_polygon() {
+        let square = vec![(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)];
+        assert_eq!(decompose_polygon(square.clone()), vec![square]);
+    }
+
+    #[test]
+    fn test_decompose_clockwise_convex_polygon() {
+        let square = vec![(0.0, 0.0), (0.0, 2.0), (2.0, 2.0), (2.0, 0.0), (0.0, 0.0)];
+        let parts = decompose_polygon(square);
+        assert_eq!(parts.len(), 1);
+        assert_eq!(get_polygon_signed_area_native(&parts[0]), 4.0);
+    }
+
+    #[test]
+    fn test_decompose_l_shape() {
+        let l_shape = vec![
+            (0.0, 0.0),
+            (10.0, 0.0),
+            (10.0, 2.0),
+            (2.0, 2.0),
+            (2.0, 10.0),
+            (0.0, 10.0),
+        ];
+        let parts = decompose_polygon(l_shape);
+        assert_eq!(parts.len(), 2);
+        assert_convex(&parts);
+        assert_eq!(total_area(&parts), 36.0);
+    }
+
+    #[test]
+    fn test_decompose_comb() {
+        // Three teeth pointing up from a base, with a collinear point on the base
+        let comb = vec![
+            (0.0, 0.0),
+            (3.0, 0.0),
+            (7.0, 0.0),
+            (7.0, 5.0),
+            (6.0, 5.0),
+            (6.0, 1.0),
+            (4.0, 1.0),
+            (4.0, 5.0),
+            (3.0, 5.0),
+            (3.0, 1.0),
+            (1.0, 1.0),
+            (1.0, 5.0),
+            (0.0, 5.0),
+        ];
+        let area = get_polygon_signed_area_native(&comb);
+        let parts = decompose_polygon(comb);
+        assert!(parts.len() >= 4 && parts.len() <= 6, "{}", parts.len());
+        assert_convex(&parts);
+        assert!((total_area(&parts) - area).abs() < 1.0e-4);
+    }
+
+    #[test]
+    fn test_decompose_degenerate() {
+        assert!(decompose_polygon(vec![]).is_empty());
+        assert!(decompose_polygon(vec![(0.0, 0.0), (1.0, 1.0), (1.0, 1.0)]).is_empty());
+    }
+}
@@ -1,9 +1,305 @@
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
 
-pub fn are_polygons_intersecting_native(
-    poly_a: &Vec<(f32, f32)>,
-    poly_b: &Vec<(f32, f32)>,
+/// An axis-aligned bounding box stored as its four edges.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    pub left: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub top: f32,
+}
+
+impl Aabb {
+    /// A box containing nothing, which never intersects anything.
+    pub const EMPTY: Aabb = Aabb {
+        left: f32::INFINITY,
+        right: f32::NEG_INFINITY,
+        bottom: f32::INFINITY,
+        top: f32::NEG_INFINITY,
+    };
+
+    pub fn from_points(points: &[(f32, f32)]) -> Aabb {
+        let mut bounds = Aabb::EMPTY;
+        for point in points {
+            bounds.left = bounds.left.min(point.0);
+            bounds.right = bounds.right.max(point.0);
+            bounds.bottom = bounds.bottom.min(point.1);
+            bounds.top = bounds.top.max(point.1);
+        }
+        bounds
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.left > self.right || self.bottom > self.top
+    }
+
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        self.left <= other.right
+            && other.left <= self.right
+            && self.bottom <= other.top
+            && other.bottom <= self.top
+    }
+
+    pub fn contains_point(&self, point: (f32, f32)) -> bool {
+        point.0 >= self.left
+            && point.0 <= self.right
+            && point.1 >= self.bottom
+            && point.1 <= self.top
+    }
+
+    /// Whether a ray with unit `direction` passes through the box within `max_distance`.
+    pub fn is_hit_by_ray(
+        &self,
+        origin: (f32, f32),
+        direction: (f32, f32),
+        max_distance: f32,
+    ) -> bool {
+        if self.is_empty() {
+            return false;
+        }
+        let mut near = 0.0_f32;
+        let mut far = max_distance;
+        for (start, step, min, max) in [
+            (origin.0, direction.0, self.left, self.right),
+            (origin.1, direction.1, self.bottom, self.top),
+        ] {
+            if step == 0.0 {
+                if start < min || start > max {
+                    return false;
+                }
+                continue;
+            }
+            let t1 = (min - start) / step;
+            let t2 = (max - start) / step;
+            near = near.max(t1.min(t2));
+            far = far.min(t1.max(t2));
+            if near > far {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// The area covered while moving the box by `displacement`.
+    pub fn swept(&self, displacement: (f32, f32)) -> Aabb {
+        Aabb {
+            left: self.left + displacement.0.min(0.0),
+            right: self.right + displacement.0.max(0.0),
+            bottom: self.bottom + displacement.1.min(0.0),
+            top: self.top + displacement.1.max(0.0),
+        }
+    }
+}
+
+/// Distance from `center` to the farthest of the points.
+pub fn get_bounding_radius(points: &[(f32, f32)], center: (f32, f32)) -> f32 {
+    let mut max_distance_squared: f32 = 0.0;
+    for point in points {
+        let dx = point.0 - center.0;
+        let dy = point.1 - center.1;
+        max_distance_squared = max_distance_squared.max(dx * dx + dy * dy);
+    }
+    max_distance_squared.sqrt()
+}
+
+/// How far `b` turns counter-clockwise from `a` as seen from `o`: positive for a left
+/// turn, negative for a right turn and zero when the three are on a line.
+pub(crate) fn cross(o: (f32, f32), a: (f32, f32), b: (f32, f32)) -> f32 {
+    (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0)
+}
+
+/// Shoelace area, positive for counter-clockwise polygons and negative for clockwise ones.
+pub fn get_polygon_signed_area_native(polygon: &[(f32, f32)]) -> f32 {
+    let mut area = 0.0;
+    for i1 in 0..polygon.len() {
+        let i2 = (i1 + 1) % polygon.len();
+        area += polygon[i1].0 * polygon[i2].1 - polygon[i2].0 * polygon[i1].1;
+    }
+    area / 2.0
+}
+
+#[pyfunction]
+pub fn get_polygon_signed_area(polygon: Vec<(f32, f32)>) -> f32 {
+    get_polygon_signed_area_native(&polygon)
+}
+
+/// Center of mass of the polygon's area.
+///
+/// Falls back to the average of the points when the area is zero, so lines and
+/// single points still give a sensible center.
+pub fn get_polygon_centroid_native(polygon: &[(f32, f32)]) -> (f32, f32) {
+    if polygon.is_empty() {
+        return (0.0, 0.0);
+    }
+    // Relative to the first point to keep precision for polygons far from the origin
+    let origin = polygon[0];
+    let mut area = 0.0;
+    let mut cx = 0.0;
+    let mut cy = 0.0;
+    for i1 in 0..polygon.len() {
+        let i2 = (i1 + 1) % polygon.len();
+        let (x1, y1) = (polygon[i1].0 - origin.0, polygon[i1].1 - origin.1);
+        let (x2, y2) = (polygon[i2].0 - origin.0, polygon[i2].1 - origin.1);
+        let cross = x1 * y2 - x2 * y1;
+        area += cross;
+        cx += (x1 + x2) * cross;
+        cy += (y1 + y2) * cross;
+    }
+    if area == 0.0 {
+        let count = polygon.len() as f32;
+        let sum = polygon
+            .iter()
+            .fold((0.0, 0.0), |sum, point| (sum.0 + point.0, sum.1 + point.1));
+        return (sum.0 / count, sum.1 / count);
+    }
+    (origin.0 + cx / (3.0 * area), origin.1 + cy / (3.0 * area))
+}
+
+#[pyfunction]
+pub fn get_polygon_centroid(polygon: Vec<(f32, f32)>) -> (f32, f32) {
+    get_polygon_centroid_native(&polygon)
+}
+
+pub fn get_polygon_perimeter_native(polygon: &[(f32, f32)]) -> f32 {
+    let mut perimeter = 0.0;
+    for i1 in 0..polygon.len() {
+        let i2 = (i1 + 1) % polygon.len();
+        perimeter += (polygon[i2].0 - polygon[i1].0).hypot(polygon[i2].1 - polygon[i1].1);
+    }
+    perimeter
+}
+
+#[pyfunction]
+pub fn get_polygon_perimeter(polygon: Vec<(f32, f32)>) -> f32 {
+    get_polygon_perimeter_native(&polygon)
+}
+
+#[pyfunction]
+pub fn is_polygon_clockwise(polygon: Vec<(f32, f32)>) -> bool {
+    get_polygon_signed_area_native(&polygon) < 0.0
+}
+
+/// Reverse the polygon in place if needed so it winds the requested way.
+pub fn normalize_polygon_winding_native(polygon: &mut [(f32, f32)], clockwise: bool) {
+    let area = get_polygon_signed_area_native(polygon);
+    if (clockwise && area > 0.0) || (!clockwise && area < 0.0) {
+        polygon.reverse();
+    }
+}
+
+/// The polygon wound counter-clockwise, or clockwise if asked for.
+#[pyfunction]
+pub fn normalize_polygon_winding(
+    mut polygon: Vec<(f32, f32)>,
+    clockwise: Option<bool>,
+) -> Vec<(f32, f32)> {
+    normalize_polygon_winding_native(&mut polygon, clockwise.unwrap_or(false));
+    polygon
+}
+
+/// Whether every corner turns the same way and the outline goes round only once.
+///
+/// Collinear and repeated points are allowed. Polygons with fewer than three points or
+/// no area are not convex.
+pub fn is_polygon_convex_native(polygon: &[(f32, f32)]) -> bool {
+    let n = polygon.len();
+    if n < 3 {
+        return false;
+    }
+    let mut sign = 0.0;
+    let mut total_turn = 0.0;
+    let mut previous_edge: Option<(f32, f32)> = None;
+    // Walk one edge past the end so the turn at the first point is included
+    for i in 0..=n {
+        let a = polygon[i % n];
+        let b = polygon[(i + 1) % n];
+        let edge = (b.0 - a.0, b.1 - a.1);
+        if edge == (0.0, 0.0) {
+            continue;
+        }
+        if let Some(previous) = previous_edge {
+            let cross = previous.0 * edge.1 - previous.1 * edge.0;
+            let dot = previous.0 * edge.0 + previous.1 * edge.1;
+            if cross != 0.0 {
+                if sign != 0.0 && cross.signum() != sign {
+                    return false;
+                }
+                sign = cross.signum();
+            } else if dot < 0.0 {
+                // Doubling back on itself
+                return false;
+            }
+            total_turn += cross.atan2(dot);
+        }
+        previous_edge = Some(edge);
+    }
+    // Going round once turns a full circle in total, a star goes round several times
+    sign != 0.0 && (total_turn.abs() - std::f32::consts::TAU).abs() < 1.0e-3
+}
+
+#[pyfunction]
+pub fn is_polygon_convex(polygon: Vec<(f32, f32)>) -> bool {
+    is_polygon_convex_native(&polygon)
+}
+
+/// Convex hull with Andrew's monotone chain, counter-clockwise and without collinear
+/// points, starting from the lowest leftmost point.
+pub fn get_convex_hull_native(points: &[(f32, f32)]) -> Vec<(f32, f32)> {
+    let mut sorted = points.to_vec();
+    sorted.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+    sorted.dedup();
+    if sorted.len() < 3 {
+        return sorted;
+    }
+
+    let mut hull: Vec<(f32, f32)> = Vec::with_capacity(sorted.len() + 1);
+    // Lower hull from left to right
+    for &point in &sorted {
+        while hull.len() >= 2 && cross(hull[hull.len() - 2], hull[hull.len() - 1], point) <= 0.0 {
+            hull.pop();
+        }
+        hull.push(point);
+    }
+    // Upper hull from right to left, never popping into the lower hull
+    let lower_length = hull.len() + 1;
+    for &point in sorted.iter().rev().skip(1) {
+        while hull.len() >= lower_length
+            && cross(hull[hull.len() - 2], hull[hull.len() - 1], point) <= 0.0
+        {
+            hull.pop();
+        }
+        hull.push(point);
+    }
+    hull.pop();
+    hull
+}
+
+#[pyfunction]
+pub fn get_convex_hull(points: Vec<(f32, f32)>) -> Vec<(f32, f32)> {
+    get_convex_hull_native(&points)
+}
+
+/// Quick rejection test run before the full polygon check.
+///
+/// Returns false when the bounding boxes or the bounding circles are apart, in
+/// which case the shapes cannot overlap.
+pub fn are_bounds_overlapping(
+    bounds_a: &Aabb,
+    circle_a: ((f32, f32), f32),
+    bounds_b: &Aabb,
+    circle_b: ((f32, f32), f32),
 ) -> bool {
+    if !bounds_a.intersects(bounds_b) {
+        return false;
+    }
+    let dx = circle_b.0 .0 - circle_a.0 .0;
+    let dy = circle_b.0 .1 - circle_a.0 .1;
+    let radii = circle_a.1 + circle_b.1;
+    dx * dx + dy * dy <= radii * radii
+}
+
+pub fn are_polygons_intersecting_native(poly_a: &[(f32, f32)], poly_b: &[(f32, f32)]) -> bool {
     // If either polygon is empty, we should just return False
     if poly_a.is_empty() || poly_b.is_empty() {
         return false;
@@ -63,61 +359,264 @@ pub fn are_polygons_intersecting_native(
 
 #[pyfunction]
 pub fn are_polygons_intersecting(poly_a: Vec<(f32, f32)>, poly_b: Vec<(f32, f32)>) -> bool {
-    // If either polygon is empty, we should just return False
+    are_polygons_intersecting_native(&poly_a, &poly_b)
+}
+
+/// How deeply two shapes overlap and the smallest move that separates them.
+///
+/// `normal` is a unit vector pointing from the second shape towards the first, and
+/// `mtv` is `normal * depth`, the translation to apply to the first shape.
+#[pyclass]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Penetration {
+    #[pyo3(get)]
+    pub depth: f32,
+    #[pyo3(get)]
+    pub normal: (f32, f32),
+    #[pyo3(get)]
+    pub mtv: (f32, f32),
+}
+
+impl Penetration {
+    pub fn new(depth: f32, normal: (f32, f32)) -> Penetration {
+        Penetration {
+            depth,
+            normal,
+            mtv: (normal.0 * depth, normal.1 * depth),
+        }
+    }
+}
+
+#[pymethods]
+impl Penetration {
+    fn __repr__(&self) -> String {
+        format!(
+            "Penetration(depth={}, normal=({}, {}), mtv=({}, {}))",
+            self.depth, self.normal.0, self.normal.1, self.mtv.0, self.mtv.1
+        )
+    }
+}
+
+fn project_polygon(polygon: &[(f32, f32)], axis: (f32, f32)) -> (f32, f32) {
+    let mut min = f32::INFINITY;
+    let mut max = f32::NEG_INFINITY;
+    for point in polygon {
+        let projected = axis.0 * point.0 + axis.1 * point.1;
+        min = min.min(projected);
+        max = max.max(projected);
+    }
+    (min, max)
+}
+
+/// Separating axis test which also keeps the axis of least overlap.
+pub fn get_polygons_penetration_native(
+    poly_a: &[(f32, f32)],
+    poly_b: &[(f32, f32)],
+) -> Option<Penetration> {
     if poly_a.is_empty() || poly_b.is_empty() {
-        return false;
+        return None;
     }
-    let polygons = [poly_a, poly_b];
-    for polygon in &polygons {
+    let mut best: Option<Penetration> = None;
+    for polygon in [poly_a, poly_b] {
         for i1 in 0..polygon.len() {
             let i2 = (i1 + 1) % polygon.len();
-            let projection_1 = polygon[i1];
-            let projection_2 = polygon[i2];
-
-            let normal = (
-                projection_2.1 - projection_1.1,
-                projection_1.0 - projection_2.0,
-            );
+            let edge = (polygon[i2].0 - polygon[i1].0, polygon[i2].1 - polygon[i1].1);
+            let length = (edge.0 * edge.0 + edge.1 * edge.1).sqrt();
+            if length == 0.0 {
+                continue;
+            }
+            let axis = (edge.1 / length, -edge.0 / length);
 
-            let mut min_a: Option<f32> = None;
-            let mut max_a: Option<f32> = None;
-            let mut min_b: Option<f32> = None;
-            let mut max_b: Option<f32> = None;
+            let (min_a, max_a) = project_polygon(poly_a, axis);
+            let (min_b, max_b) = project_polygon(poly_b, axis);
+            if max_a <= min_b || max_b <= min_a {
+                return None;
+            }
 
-            for point in &polygons[0] {
-                let projected = normal.0 * point.0 + normal.1 * point.1;
-                match min_a {
-                    Some(x) if projected < x => min_a = Some(projected),
-                    Some(_x) => {}
-                    None => min_a = Some(projected),
-                }
-                match max_a {
-                    Some(x) if projected > x => max_a = Some(projected),
-                    Some(_x) => {}
-                    None => max_a = Some(projected),
-                }
+            // Pushing A along +axis or -axis, whichever is shorter
+            let forward = max_b - min_a;
+            let backward = max_a - min_b;
+            let (depth, normal) = if forward < backward {
+                (forward, axis)
+            } else {
+                (backward, (-axis.0, -axis.1))
+            };
+            if best.is_none_or(|b| depth < b.depth) {
+                best = Some(Penetration::new(depth, normal));
             }
+        }
+    }
+    best
+}
 
-            for point in &polygons[1] {
-                let projected = normal.0 * point.0 + normal.1 * point.1;
-                match min_b {
-                    Some(x) if projected < x => min_b = Some(projected),
-                    Some(_x) => {}
-                    None => min_b = Some(projected),
+#[pyfunction]
+pub fn get_polygons_penetration(
+    poly_a: Vec<(f32, f32)>,
+    poly_b: Vec<(f32, f32)>,
+) -> Option<Penetration> {
+    get_polygons_penetration_native(&poly_a, &poly_b)
+}
+
+/// When a moving shape first touches another.
+///
+/// `time` is the fraction of the displacement travelled before the shapes touch, from
+/// 0.0 to 1.0, and `normal` is a unit vector pointing from the second shape towards the
+/// first, as in `Penetration`. Shapes which already overlap have a time of 0.0 and the
+/// normal of their smallest penetration.
+#[pyclass]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TimeOfImpact {
+    #[pyo3(get)]
+    pub time: f32,
+    #[pyo3(get)]
+    pub normal: (f32, f32),
+}
+
+impl TimeOfImpact {
+    pub fn new(time: f32, normal: (f32, f32)) -> TimeOfImpact {
+        TimeOfImpact { time, normal }
+    }
+}
+
+#[pymethods]
+impl TimeOfImpact {
+    fn __repr__(&self) -> String {
+        format!(
+            "TimeOfImpact(time={}, normal=({}, {}))",
+            self.time, self.normal.0, self.normal.1
+        )
+    }
+}
+
+/// Swept separating axis test for two convex polygons each moving in a straight line.
+///
+/// Shapes which only touch without moving into each other, such as a box sliding along
+/// a wall, do not count as an impact.
+pub fn get_polygons_time_of_impact_native(
+    poly_a: &[(f32, f32)],
+    displacement_a: (f32, f32),
+    poly_b: &[(f32, f32)],
+    displacement_b: (f32, f32),
+) -> Option<TimeOfImpact> {
+    if poly_a.is_empty() || poly_b.is_empty() {
+        return None;
+    }
+    // Sweep A against a stationary B
+    let velocity = (
+        displacement_a.0 - displacement_b.0,
+        displacement_a.1 - displacement_b.1,
+    );
+    let mut first = f32::NEG_INFINITY;
+    let mut first_normal = (0.0, 0.0);
+    let mut last = f32::INFINITY;
+    for polygon in [poly_a, poly_b] {
+        for i1 in 0..polygon.len() {
+            let i2 = (i1 + 1) % polygon.len();
+            let edge = (polygon[i2].0 - polygon[i1].0, polygon[i2].1 - polygon[i1].1);
+            let Some(axis) = normalize((edge.1, -edge.0)) else {
+                continue;
+            };
+
+            let (min_a, max_a) = project_polygon(poly_a, axis);
+            let (min_b, max_b) = project_polygon(poly_b, axis);
+            let speed = velocity.0 * axis.0 + velocity.1 * axis.1;
+            let (enter, exit, normal) = if max_a <= min_b {
+                if speed <= 0.0 {
+                    return None;
                 }
-                match max_b {
-                    Some(x) if projected > x => max_b = Some(projected),
-                    Some(_x) => {}
-                    None => max_b = Some(projected),
+                let normal = (-axis.0, -axis.1);
+                ((min_b - max_a) / speed, (max_b - min_a) / speed, normal)
+            } else if max_b <= min_a {
+                if speed >= 0.0 {
+                    return None;
                 }
-            }
+                ((max_b - min_a) / speed, (min_b - max_a) / speed, axis)
+            } else {
+                let exit = if speed > 0.0 {
+                    (max_b - min_a) / speed
+                } else if speed < 0.0 {
+                    (min_b - max_a) / speed
+                } else {
+                    f32::INFINITY
+                };
+                (f32::NEG_INFINITY, exit, axis)
+            };
 
-            if max_a <= min_b || max_b <= min_a {
-                return false;
+            if enter > first {
+                first = enter;
+                first_normal = normal;
+            }
+            last = last.min(exit);
+            if first >= last || first > 1.0 {
+                return None;
             }
         }
     }
-    true
+
+    if first == f32::NEG_INFINITY {
+        get_polygons_penetration_native(poly_a, poly_b)
+            .map(|penetration| TimeOfImpact::new(0.0, penetration.normal))
+    } else {
+        Some(TimeOfImpact::new(first, first_normal))
+    }
+}
+
+/// When `poly_a` moving by `displacement_a` first touches `poly_b` moving by
+/// `displacement_b`, or `None` if they never do.
+#[pyfunction]
+pub fn get_polygons_time_of_impact(
+    poly_a: Vec<(f32, f32)>,
+    displacement_a: (f32, f32),
+    poly_b: Vec<(f32, f32)>,
+    displacement_b: Option<(f32, f32)>,
+) -> Option<TimeOfImpact> {
+    get_polygons_time_of_impact_native(
+        &poly_a,
+        displacement_a,
+        &poly_b,
+        displacement_b.unwrap_or((0.0, 0.0)),
+    )
+}
+
+fn normalize(vector: (f32, f32)) -> Option<(f32, f32)> {
+    let length = (vector.0 * vector.0 + vector.1 * vector.1).sqrt();
+    if length == 0.0 {
+        None
+    } else {
+        Some((vector.0 / length, vector.1 / length))
+    }
+}
+
+/// Remove the part of `velocity` heading into a surface with the given normal.
+///
+/// Velocities moving away from the surface, or a zero normal, are returned unchanged.
+#[pyfunction]
+pub fn slide_vector(velocity: (f32, f32), normal: (f32, f32)) -> (f32, f32) {
+    reflect_vector(velocity, normal, Some(0.0))
+}
+
+/// Bounce `velocity` off a surface with the given normal.
+///
+/// A restitution of 1.0 (the default) is a perfect reflection and 0.0 is the same as
+/// sliding. Velocities moving away from the surface, or a zero normal, are returned unchanged.
+#[pyfunction]
+pub fn reflect_vector(
+    velocity: (f32, f32),
+    normal: (f32, f32),
+    restitution: Option<f32>,
+) -> (f32, f32) {
+    let Some(normal) = normalize(normal) else {
+        return velocity;
+    };
+    let dot = velocity.0 * normal.0 + velocity.1 * normal.1;
+    if dot >= 0.0 {
+        return velocity;
+    }
+    let factor = (1.0 + restitution.unwrap_or(1.0)) * dot;
+    (
+        velocity.0 - normal.0 * factor,
+        velocity.1 - normal.1 * factor,
+    )
 }
 
 #[pyfunction]
@@ -160,8 +659,33 @@ pub fn are_lines_intersecting(
     || ((o4 == 0) && is_point_in_box(p2, q1, q2))
 }
 
+/// Where segment `p1`-`q1` crosses segment `p2`-`q2`, if it does. Parallel segments have
+/// no single crossing point and give `None`.
+pub(crate) fn get_segments_intersection_point(
+    p1: (f32, f32),
+    q1: (f32, f32),
+    p2: (f32, f32),
+    q2: (f32, f32),
+) -> Option<(f32, f32)> {
+    let r = (q1.0 - p1.0, q1.1 - p1.1);
+    let s = (q2.0 - p2.0, q2.1 - p2.1);
+    let denominator = r.0 * s.1 - r.1 * s.0;
+    if denominator == 0.0 {
+        return None;
+    }
+    let offset = (p2.0 - p1.0, p2.1 - p1.1);
+    let t = (offset.0 * s.1 - offset.1 * s.0) / denominator;
+    let u = (offset.0 * r.1 - offset.1 * r.0) / denominator;
+    ((0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u))
+        .then_some((p1.0 + r.0 * t, p1.1 + r.1 * t))
+}
+
 #[pyfunction]
 pub fn is_point_in_polygon(x: f32, y: f32, polygon: Vec<(f32, f32)>) -> bool {
+    is_point_in_polygon_native(x, y, &polygon)
+}
+
+pub fn is_point_in_polygon_native(x: f32, y: f32, polygon: &[(f32, f32)]) -> bool {
     let p = (x, y);
     let n = polygon.len();
 
@@ -218,10 +742,778 @@ pub fn is_point_in_polygon(x: f32, y: f32, polygon: Vec<(f32, f32)>) -> bool {
     count % 2 == 1
 }
 
+/// Squared distance from `point` to the closest point of the segment `a`-`b`.
+pub fn get_point_segment_distance_squared(point: (f32, f32), a: (f32, f32), b: (f32, f32)) -> f32 {
+    let ab = (b.0 - a.0, b.1 - a.1);
+    let ap = (point.0 - a.0, point.1 - a.1);
+    let length_squared = ab.0 * ab.0 + ab.1 * ab.1;
+    let t = if length_squared == 0.0 {
+        0.0
+    } else {
+        ((ap.0 * ab.0 + ap.1 * ab.1) / length_squared).clamp(0.0, 1.0)
+    };
+    let dx = ap.0 - ab.0 * t;
+    let dy = ap.1 - ab.1 * t;
+    dx * dx + dy * dy
+}
+
+/// Where a ray first hits a shape.
+///
+/// `distance` is measured from the ray's origin along its direction, and `normal` is the
+/// unit surface normal at `point`, facing back towards the ray. A ray starting inside
+/// a shape hits it at its origin, with a distance of 0.0 and the normal pointing back
+/// along the ray.
+#[pyclass]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RayHit {
+    #[pyo3(get)]
+    pub point: (f32, f32),
+    #[pyo3(get)]
+    pub normal: (f32, f32),
+    #[pyo3(get)]
+    pub distance: f32,
+}
+
+impl RayHit {
+    pub fn new(
+        origin: (f32, f32),
+        direction: (f32, f32),
+        distance: f32,
+        normal: (f32, f32),
+    ) -> RayHit {
+        RayHit {
+            point: (
+                origin.0 + direction.0 * distance,
+                origin.1 + direction.1 * distance,
+            ),
+            normal,
+            distance,
+        }
+    }
+
+    /// A hit for a ray starting inside a shape.
+    pub fn at_origin(origin: (f32, f32), direction: (f32, f32)) -> RayHit {
+        RayHit::new(origin, direction, 0.0, (-direction.0, -direction.1))
+    }
+
+    /// The nearer of two optional hits.
+    pub fn nearest(first: Option<RayHit>, second: Option<RayHit>) -> Option<RayHit> {
+        match (first, second) {
+            (Some(a), Some(b)) => Some(if b.distance < a.distance { b } else { a }),
+            (a, b) => a.or(b),
+        }
+    }
+}
+
+#[pymethods]
+impl RayHit {
+    fn __repr__(&self) -> String {
+        format!(
+            "RayHit(point=({}, {}), normal=({}, {}), distance={})",
+            self.point.0, self.point.1, self.normal.0, self.normal.1, self.distance
+        )
+    }
+}
+
+/// The unit direction of a ray, raising `ValueError` for a zero vector.
+pub fn get_ray_direction(direction: (f32, f32)) -> PyResult<(f32, f32)> {
+    normalize(direction).ok_or_else(|| PyValueError::new_err("ray direction can not be zero"))
+}
+
+/// Plain even-odd test. Points exactly on an edge may count as either side.
+fn is_point_enclosed(point: (f32, f32), polygon: &[(f32, f32)]) -> bool {
+    let mut inside = false;
+    let mut j = polygon.len().wrapping_sub(1);
+    for (i, a) in polygon.iter().enumerate() {
+        let b = polygon[j];
+        if (a.1 > point.1) != (b.1 > point.1)
+            && point.0 < (b.0 - a.0) * (point.1 - a.1) / (b.1 - a.1) + a.0
+        {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+/// Cast a ray with unit `direction` against the edges of a polygon, which does not need
+/// to be convex.
+pub fn cast_ray_against_polygon_native(
+    origin: (f32, f32),
+    direction: (f32, f32),
+    polygon: &[(f32, f32)],
+    max_distance: f32,
+) -> Option<RayHit> {
+    if polygon.len() < 3 || max_distance < 0.0 {
+        return None;
+    }
+    if is_point_enclosed(origin, polygon) {
+        return Some(RayHit::at_origin(origin, direction));
+    }
+    let mut nearest: Option<(f32, (f32, f32))> = None;
+    for i1 in 0..polygon.len() {
+        let a = polygon[i1];
+        let b = polygon[(i1 + 1) % polygon.len()];
+        let edge = (b.0 - a.0, b.1 - a.1);
+        let denominator = direction.0 * edge.1 - direction.1 * edge.0;
+        if denominator == 0.0 {
+            continue;
+        }
+        let offset = (a.0 - origin.0, a.1 - origin.1);
+        let distance = (offset.0 * edge.1 - offset.1 * edge.0) / denominator;
+        let along_edge = (offset.0 * direction.1 - offset.1 * direction.0) / denominator;
+        if distance < 0.0
+            || distance > max_distance
+            || !(0.0..=1.0).contains(&along_edge)
+            || nearest.is_some_and(|(nearest, _)| nearest <= distance)
+        {
+            continue;
+        }
+        let Some(mut normal) = normalize((edge.1, -edge.0)) else {
+            continue;
+        };
+        if normal.0 * direction.0 + normal.1 * direction.1 > 0.0 {
+            normal = (-normal.0, -normal.1);
+        }
+        nearest = Some((distance, normal));
+    }
+    nearest.map(|(distance, normal)| RayHit::new(origin, direction, distance, normal))
+}
+
+/// Cast a ray with unit `direction` against a circle.
+pub fn cast_ray_against_circle_native(
+    origin: (f32, f32),
+    direction: (f32, f32),
+    center: (f32, f32),
+    radius: f32,
+    max_distance: f32,
+) -> Option<RayHit> {
+    let offset = (origin.0 - center.0, origin.1 - center.1);
+    let c = offset.0 * offset.0 + offset.1 * offset.1 - radius * radius;
+    if c < 0.0 {
+        return Some(RayHit::at_origin(origin, direction));
+    }
+    let b = offset.0 * direction.0 + offset.1 * direction.1;
+    let discriminant = b * b - c;
+    if b > 0.0 || discriminant < 0.0 {
+        return None;
+    }
+    let distance = -b - discriminant.sqrt();
+    if distance > max_distance {
+        return None;
+    }
+    let hit = RayHit::new(origin, direction, distance, (0.0, 0.0));
+    let normal = normalize((hit.point.0 - center.0, hit.point.1 - center.1))
+        .unwrap_or((-direction.0, -direction.1));
+    Some(RayHit { normal, ..hit })
+}
+
+/// Cast a ray with unit `direction` against a capsule, the first hit on either end
+/// circle or the rectangle between them.
+pub fn cast_ray_against_capsule_native(
+    origin: (f32, f32),
+    direction: (f32, f32),
+    start: (f32, f32),
+    end: (f32, f32),
+    radius: f32,
+    max_distance: f32,
+) -> Option<RayHit> {
+    let mut nearest = RayHit::nearest(
+        cast_ray_against_circle_native(origin, direction, start, radius, max_distance),
+        cast_ray_against_circle_native(origin, direction, end, radius, max_distance),
+    );
+    if let Some(side) = normalize((start.1 - end.1, end.0 - start.0)) {
+        let side = (side.0 * radius, side.1 * radius);
+        let body = [
+            (start.0 + side.0, start.1 + side.1),
+            (end.0 + side.0, end.1 + side.1),
+            (end.0 - side.0, end.1 - side.1),
+            (start.0 - side.0, start.1 - side.1),
+        ];
+        nearest = RayHit::nearest(
+            nearest,
+            cast_ray_against_polygon_native(origin, direction, &body, max_distance),
+        );
+    }
+    nearest
+}
+
+/// Where a ray from `origin` heading in `direction` first hits `polygon`, or `None`
+/// if it misses or the hit is further than `max_distance`, which defaults to no limit.
+#[pyfunction]
+pub fn cast_ray_against_polygon(
+    origin: (f32, f32),
+    direction: (f32, f32),
+    polygon: Vec<(f32, f32)>,
+    max_distance: Option<f32>,
+) -> PyResult<Option<RayHit>> {
+    Ok(cast_ray_against_polygon_native(
+        origin,
+        get_ray_direction(direction)?,
+        &polygon,
+        max_distance.unwrap_or(f32::INFINITY),
+    ))
+}
+
+#[pyfunction]
+pub fn are_circles_intersecting(
+    center_a: (f32, f32),
+    radius_a: f32,
+    center_b: (f32, f32),
+    radius_b: f32,
+) -> bool {
+    let dx = center_b.0 - center_a.0;
+    let dy = center_b.1 - center_a.1;
+    let radii = radius_a + radius_b;
+    dx * dx + dy * dy < radii * radii
+}
+
+pub fn is_circle_intersecting_polygon_native(
+    center: (f32, f32),
+    radius: f32,
+    polygon: &[(f32, f32)],
+) -> bool {
+    if polygon.is_empty() {
+        return false;
+    }
+    // A circle entirely inside the polygon touches none of its edges
+    if is_point_in_polygon_native(center.0, center.1, polygon) {
+        return true;
+    }
+    let radius_squared = radius * radius;
+    for i1 in 0..polygon.len() {
+        let i2 = (i1 + 1) % polygon.len();
+        if get_point_segment_distance_squared(center, polygon[i1], polygon[i2]) < radius_squared {
+            return true;
+        }
+    }
+    false
+}
+
+#[pyfunction]
+pub fn is_circle_intersecting_polygon(
+    center: (f32, f32),
+    radius: f32,
+    polygon: Vec<(f32, f32)>,
+) -> bool {
+    is_circle_intersecting_polygon_native(center, radius, &polygon)
+}
+
+/// Squared distance between the closest points of segments `a1`-`b1` and `a2`-`b2`.
+pub fn get_segments_distance_squared(
+    a1: (f32, f32),
+    b1: (f32, f32),
+    a2: (f32, f32),
+    b2: (f32, f32),
+) -> f32 {
+    if are_lines_intersecting(a1, b1, a2, b2) {
+        return 0.0;
+    }
+    get_point_segment_distance_squared(a1, a2, b2)
+        .min(get_point_segment_distance_squared(b1, a2, b2))
+        .min(get_point_segment_distance_squared(a2, a1, b1))
+        .min(get_point_segment_distance_squared(b2, a1, b1))
+}
+
+/// Capsules are the points within `radius` of a segment. A circle is a capsule whose
+/// segment has the same start and end.
+#[pyfunction]
+pub fn are_capsules_intersecting(
+    start_a: (f32, f32),
+    end_a: (f32, f32),
+    radius_a: f32,
+    start_b: (f32, f32),
+    end_b: (f32, f32),
+    radius_b: f32,
+) -> bool {
+    let radii = radius_a + radius_b;
+    get_segments_distance_squared(start_a, end_a, start_b, end_b) < radii * radii
+}
+
+pub fn is_capsule_intersecting_polygon_native(
+    start: (f32, f32),
+    end: (f32, f32),
+    radius: f32,
+    polygon: &[(f32, f32)],
+) -> bool {
+    if polygon.is_empty() {
+        return false;
+    }
+    if is_point_in_polygon_native(start.0, start.1, polygon) {
+        return true;
+    }
+    let radius_squared = radius * radius;
+    for i1 in 0..polygon.len() {
+        let i2 = (i1 + 1) % polygon.len();
+        if get_segments_distance_squared(start, end, polygon[i1], polygon[i2]) < radius_squared {
+            return true;
+        }
+    }
+    false
+}
+
+#[pyfunction]
+pub fn is_capsule_intersecting_polygon(
+    start: (f32, f32),
+    end: (f32, f32),
+    radius: f32,
+    polygon: Vec<(f32, f32)>,
+) -> bool {
+    is_capsule_intersecting_polygon_native(start, end, radius, &polygon)
+}
+
+/// Kept vertices of a polygon (`closed`) or polyline as indices into `points`, with
+/// the edges running between consecutive indices.
+fn get_path_edges(indices: &[usize], closed: bool) -> impl Iterator<Item = (usize, usize)> + '_ {
+    let edge_count = if closed {
+        indices.len()
+    } else {
+        indices.len().saturating_sub(1)
+    };
+    (0..edge_count).map(move |k| (indices[k], indices[(k + 1) % indices.len()]))
+}
+
+/// Whether the segment `a`-`b` crosses or touches an edge of the path that does not
+/// share an endpoint with it.
+fn is_segment_crossing_path(
+    points: &[(f32, f32)],
+    indices: &[usize],
+    closed: bool,
+    a: usize,
+    b: usize,
+) -> bool {
+    get_path_edges(indices, closed).any(|(c, d)| {
+        c != a
+            && c != b
+            && d != a
+            && d != b
+            && are_lines_intersecting(points[a], points[b], points[c], points[d])
+    })
+}
+
+/// Original indices strictly between `start` and `end`, wrapping round for polygons.
+fn get_skipped_indices(start: usize, end: usize, count: usize) -> impl Iterator<Item = usize> {
+    (1..(end + count - start) % count).map(move |step| (start + step) % count)
+}
+
+/// Ramer-Douglas-Peucker over `order[start..=end]`, marking the kept vertices.
+fn mark_rdp_vertices(
+    points: &[(f32, f32)],
+    order: &[usize],
+    start: usize,
+    end: usize,
+    tolerance_squared: f32,
+    keep: &mut [bool],
+) {
+    let (a, b) = (points[order[start]], points[order[end]]);
+    let farthest = (start + 1..end)
+        .map(|k| {
+            (
+                k,
+                get_point_segment_distance_squared(points[order[k]], a, b),
+            )
+        })
+        .max_by(|x, y| x.1.total_cmp(&y.1));
+    if let Some((k, distance)) = farthest {
+        if distance > tolerance_squared {
+            keep[order[k]] = true;
+            mark_rdp_vertices(points, order, start, k, tolerance_squared, keep);
+            mark_rdp_vertices(points, order, k, end, tolerance_squared, keep);
+        }
+    }
+}
+
+/// Put back skipped vertices until no two edges of the simplified path cross.
+///
+/// For every crossing edge the skipped vertex farthest from it is restored, so this
+/// ends at the latest when the original path is back.
+fn restore_until_simple(points: &[(f32, f32)], keep: &mut [bool], closed: bool) {
+    loop {
+        let indices: Vec<usize> = (0..points.len()).filter(|&i| keep[i]).collect();
+        let crossing: Vec<(usize, usize)> = get_path_edges(&indices, closed)
+            .filter(|&(a, b)| is_segment_crossing_path(points, &indices, closed, a, b))
+            .collect();
+        let mut restored = false;
+        for (a, b) in crossing {
+            let farthest = get_skipped_indices(a, b, points.len()).max_by(|&x, &y| {
+                get_point_segment_distance_squared(points[x], points[a], points[b]).total_cmp(
+                    &get_point_segment_distance_squared(points[y], points[a], points[b]),
+                )
+            });
+            if let Some(index) = farthest {
+                keep[index] = true;
+                restored = true;
+            }
+        }
+        if !restored {
+            return;
+        }
+    }
+}
+
+/// Ramer-Douglas-Peucker simplification of an open polyline.
+///
+/// No removed point is further than `tolerance` from the result. The end points are
+/// always kept, and if the input does not cross itself neither does the result.
+pub fn simplify_polyline_rdp_native(points: &[(f32, f32)], tolerance: f32) -> Vec<(f32, f32)> {
+    if points.len() <= 2 {
+        return points.to_vec();
+    }
+    let order: Vec<usize> = (0..points.len()).collect();
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+    mark_rdp_vertices(
+        points,
+        &order,
+        0,
+        points.len() - 1,
+        tolerance * tolerance,
+        &mut keep,
+    );
+    restore_until_simple(points, &mut keep, false);
+    (0..points.len())
+        .filter(|&i| keep[i])
+        .map(|i| points[i])
+        .collect()
+}
+
+/// Ramer-Douglas-Peucker simplification of a closed polygon.
+///
+/// The polygon is split at its leftmost point and the point farthest from it, so the
+/// result does not depend on where the outline starts. Keeps the original order, at
+/// least three points, and stays simple if the input is.
+pub fn simplify_polygon_rdp_native(polygon: &[(f32, f32)], tolerance: f32) -> Vec<(f32, f32)> {
+    let count = polygon.len();
+    if count <= 3 {
+        return polygon.to_vec();
+    }
+    let distance_squared = |a: (f32, f32), b: (f32, f32)| (a.0 - b.0).powi(2) + (a.1 - b.1).powi(2);
+    let first = (0..count)
+        .min_by(|&a, &b| {
+            polygon[a]
+                .0
+                .total_cmp(&polygon[b].0)
+                .then(polygon[a].1.total_cmp(&polygon[b].1))
+        })
+        .unwrap();
+    // Walk the outline from the first point all the way back round to it
+    let order: Vec<usize> = (0..=count).map(|step| (first + step) % count).collect();
+    let opposite = (1..count)
+        .max_by(|&a, &b| {
+            distance_squared(polygon[first], polygon[order[a]])
+                .total_cmp(&distance_squared(polygon[first], polygon[order[b]]))
+        })
+        .unwrap();
+
+    let tolerance_squared = tolerance * tolerance;
+    let mut keep = vec![false; count];
+    keep[first] = true;
+    keep[order[opposite]] = true;
+    mark_rdp_vertices(polygon, &order, 0, opposite, tolerance_squared, &mut keep);
+    mark_rdp_vertices(
+        polygon,
+        &order,
+        opposite,
+        count,
+        tolerance_squared,
+        &mut keep,
+    );
+
+    if keep.iter().filter(|&&kept| kept).count() < 3 {
+        let (a, b) = (polygon[first], polygon[order[opposite]]);
+        let apex = (0..count)
+            .filter(|&i| !keep[i])
+            .max_by(|&x, &y| {
+                get_point_segment_distance_squared(polygon[x], a, b)
+                    .total_cmp(&get_point_segment_distance_squared(polygon[y], a, b))
+            })
+            .unwrap();
+        keep[apex] = true;
+    }
+    restore_until_simple(polygon, &mut keep, true);
+    (0..count)
+        .filter(|&i| keep[i])
+        .map(|i| polygon[i])
+        .collect()
+}
+
+fn get_triangle_area(a: (f32, f32), b: (f32, f32), c: (f32, f32)) -> f32 {
+    ((b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0)).abs() / 2.0
+}
+
+/// Visvalingam-Whyatt: repeatedly drop the vertex forming the smallest triangle with
+/// its neighbours while that area is below `tolerance`, skipping removals that would
+/// make the path cross itself.
+fn simplify_vw(points: &[(f32, f32)], tolerance: f32, closed: bool) -> Vec<(f32, f32)> {
+    let mut indices: Vec<usize> = (0..points.len()).collect();
+    let min_count = if closed { 3 } else { 2 };
+    while indices.len() > min_count {
+        let count = indices.len();
+        let removable = if closed { 0..count } else { 1..count - 1 };
+        let neighbours = |k: usize| (indices[(k + count - 1) % count], indices[(k + 1) % count]);
+        let mut candidates: Vec<(f32, usize)> = removable
+            .map(|k| {
+                let (prev, next) = neighbours(k);
+                let area = get_triangle_area(points[prev], points[indices[k]], points[next]);
+                (area, k)
+            })
+            .filter(|&(area, _)| area < tolerance)
+            .collect();
+        candidates.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let removal = candidates.iter().map(|&(_, k)| k).find(|&k| {
+            let (prev, next) = neighbours(k);
+            let mut remaining = indices.clone();
+            remaining.remove(k);
+            !is_segment_crossing_path(points, &remaining, closed, prev, next)
+        });
+        match removal {
+            Some(k) => {
+                indices.remove(k);
+            }
+            None => break,
+        }
+    }
+    indices.iter().map(|&i| points[i]).collect()
+}
+
+/// Visvalingam-Whyatt simplification of an open polyline, with `tolerance` as the
+/// smallest triangle area a kept vertex may form with its neighbours.
+pub fn simplify_polyline_vw_native(points: &[(f32, f32)], tolerance: f32) -> Vec<(f32, f32)> {
+    simplify_vw(points, tolerance, false)
+}
+
+/// Visvalingam-Whyatt simplification of a closed polygon, keeping at least three points.
+pub fn simplify_polygon_vw_native(polygon: &[(f32, f32)], tolerance: f32) -> Vec<(f32, f32)> {
+    simplify_vw(polygon, tolerance, true)
+}
+
+/// Drop repeated points and vertices lying within `tolerance` of the line through
+/// their neighbours, including a closing point equal to the first.
+pub fn remove_collinear_points_native(polygon: &[(f32, f32)], tolerance: f32) -> Vec<(f32, f32)> {
+    let mut points: Vec<(f32, f32)> = Vec::with_capacity(polygon.len());
+    for point in polygon {
+        if points.last() != Some(point) {
+            points.push(*point);
+        }
+    }
+    while points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+
+    let mut indices: Vec<usize> = (0..points.len()).collect();
+    let mut k = 0;
+    let mut since_removal = 0;
+    while indices.len() > 3 && since_removal < indices.len() {
+        let count = indices.len();
+        k %= count;
+        let prev = indices[(k + count - 1) % count];
+        let next = indices[(k + 1) % count];
+        let (a, p, b) = (points[prev], points[indices[k]], points[next]);
+        let length = (b.0 - a.0).hypot(b.1 - a.1);
+        let distance = if length == 0.0 {
+            (p.0 - a.0).hypot(p.1 - a.1)
+        } else {
+            ((b.0 - a.0) * (p.1 - a.1) - (b.1 - a.1) * (p.0 - a.0)).abs() / length
+        };
+        let mut remaining = indices.clone();
+        remaining.remove(k);
+        if distance <= tolerance
+            && (tolerance == 0.0
+                || !is_segment_crossing_path(&points, &remaining, true, prev, next))
+        {
+            indices = remaining;
+            since_removal = 0;
+        } else {
+            k += 1;
+            since_removal += 1;
+        }
+    }
+    indices.iter().map(|&i| points[i]).collect()
+}
+
+#[pyfunction]
+pub fn simplify_polyline_rdp(points: Vec<(f32, f32)>, tolerance: f32) -> Vec<(f32, f32)> {
+    simplify_polyline_rdp_native(&points, tolerance)
+}
+
+#[pyfunction]
+pub fn simplify_polygon_rdp(polygon: Vec<(f32, f32)>, tolerance: f32) -> Vec<(f32, f32)> {
+    simplify_polygon_rdp_native(&polygon, tolerance)
+}
+
+#[pyfunction]
+pub fn simplify_polyline_vw(points: Vec<(f32, f32)>, tolerance: f32) -> Vec<(f32, f32)> {
+    simplify_polyline_vw_native(&points, tolerance)
+}
+
+#[pyfunction]
+pub fn simplify_polygon_vw(polygon: Vec<(f32, f32)>, tolerance: f32) -> Vec<(f32, f32)> {
+    simplify_polygon_vw_native(&polygon, tolerance)
+}
+
+#[pyfunction]
+pub fn remove_collinear_points(
+    polygon: Vec<(f32, f32)>,
+    tolerance: Option<f32>,
+) -> Vec<(f32, f32)> {
+    remove_collinear_points_native(&polygon, tolerance.unwrap_or(0.0))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_aabb_from_points() {
+        let bounds = Aabb::from_points(&[(1.0, -2.0), (-3.0, 4.0), (5.0, 0.0)]);
+        assert_eq!(
+            bounds,
+            Aabb {
+                left: -3.0,
+                right: 5.0,
+                bottom: -2.0,
+                top: 4.0
+            }
+        );
+        assert!(Aabb::from_points(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_aabb_intersects() {
+        let a = Aabb::from_points(&[(0.0, 0.0), (10.0, 10.0)]);
+        let b = Aabb::from_points(&[(5.0, 5.0), (15.0, 15.0)]);
+        let c = Aabb::from_points(&[(11.0, 0.0), (12.0, 1.0)]);
+        assert!(a.intersects(&b));
+        assert!(!a.intersects(&c));
+        assert!(!a.intersects(&Aabb::EMPTY));
+        assert!(a.contains_point((10.0, 0.0)));
+        assert!(!a.contains_point((10.5, 0.0)));
+    }
+
+    #[test]
+    fn test_get_bounding_radius() {
+        let points = vec![(3.0, 4.0), (-1.0, 0.0), (0.0, -2.0)];
+        assert_eq!(get_bounding_radius(&points, (0.0, 0.0)), 5.0);
+        assert_eq!(get_bounding_radius(&[], (0.0, 0.0)), 0.0);
+    }
+
+    #[test]
+    fn test_get_polygon_signed_area() {
+        let square = vec![(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)];
+        assert_eq!(get_polygon_signed_area_native(&square), 4.0);
+        let reversed: Vec<(f32, f32)> = square.into_iter().rev().collect();
+        assert_eq!(get_polygon_signed_area_native(&reversed), -4.0);
+        assert_eq!(get_polygon_signed_area_native(&[]), 0.0);
+    }
+
+    #[test]
+    fn test_get_polygon_centroid() {
+        let square = vec![(10.0, 10.0), (12.0, 10.0), (12.0, 12.0), (10.0, 12.0)];
+        assert_eq!(get_polygon_centroid(square), (11.0, 11.0));
+        let triangle = vec![(0.0, 0.0), (0.0, 3.0), (3.0, 0.0)];
+        assert_eq!(get_polygon_centroid(triangle), (1.0, 1.0));
+        let line = vec![(0.0, 0.0), (4.0, 0.0)];
+        assert_eq!(get_polygon_centroid(line), (2.0, 0.0));
+        assert_eq!(get_polygon_centroid(vec![]), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_get_polygon_perimeter() {
+        let triangle = vec![(0.0, 0.0), (3.0, 0.0), (0.0, 4.0)];
+        assert_eq!(get_polygon_perimeter(triangle), 12.0);
+        assert_eq!(get_polygon_perimeter(vec![]), 0.0);
+    }
+
+    #[test]
+    fn test_polygon_winding() {
+        let square = vec![(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)];
+        let reversed: Vec<(f32, f32)> = square.iter().rev().copied().collect();
+        assert!(!is_polygon_clockwise(square.clone()));
+        assert!(is_polygon_clockwise(reversed.clone()));
+        assert_eq!(normalize_polygon_winding(reversed.clone(), None), square);
+        assert_eq!(normalize_polygon_winding(square.clone(), None), square);
+        assert_eq!(normalize_polygon_winding(square, Some(true)), reversed);
+    }
+
+    #[test]
+    fn test_is_polygon_convex() {
+        let square = vec![(0.0, 0.0), (1.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)];
+        assert!(is_polygon_convex(square.clone()));
+        assert!(is_polygon_convex(square.into_iter().rev().collect()));
+        let arrow = vec![(0.0, 0.0), (2.0, 1.0), (0.0, 2.0), (1.0, 1.0)];
+        assert!(!is_polygon_convex(arrow));
+        let star = vec![
+            (0.0, 3.0),
+            (1.76, -2.43),
+            (-2.85, 0.93),
+            (2.85, 0.93),
+            (-1.76, -2.43),
+        ];
+        assert!(!is_polygon_convex(star));
+        assert!(!is_polygon_convex(vec![(0.0, 0.0), (1.0, 0.0), (2.0, 0.0)]));
+        assert!(!is_polygon_convex(vec![(0.0, 0.0), (1.0, 1.0)]));
+    }
+
+    #[test]
+    fn test_get_convex_hull() {
+        let points = vec![
+            (1.0, 1.0),
+            (0.0, 0.0),
+            (2.0, 2.0),
+            (2.0, 0.0),
+            (1.0, 0.0),
+            (0.0, 2.0),
+            (2.0, 0.0),
+            (0.5, 1.5),
+        ];
+        assert_eq!(
+            get_convex_hull(points),
+            vec![(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)]
+        );
+        assert_eq!(
+            get_convex_hull(vec![(1.0, 1.0), (0.0, 0.0), (1.0, 1.0)]),
+            vec![(0.0, 0.0), (1.0, 1.0)]
+        );
+        assert_eq!(
+            get_convex_hull(vec![(0.0, 0.0), (1.0, 1.0), (2.0, 2.0)]),
+            vec![(0.0, 0.0), (2.0, 2.0)]
+        );
+    }
+
+    #[test]
+    fn test_are_bounds_overlapping() {
+        let square = vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)];
+        let bounds = Aabb::from_points(&square);
+        let circle = ((5.0, 5.0), get_bounding_radius(&square, (5.0, 5.0)));
+        assert!(are_bounds_overlapping(&bounds, circle, &bounds, circle));
+
+        // Boxes overlap but the bounding circles of the corners do not
+        let small = Aabb::from_points(&[(9.9, 9.9), (10.0, 10.0)]);
+        assert!(bounds.intersects(&small));
+        assert!(are_bounds_overlapping(
+            &bounds,
+            circle,
+            &small,
+            ((10.0, 10.0), 0.1)
+        ));
+        assert!(!are_bounds_overlapping(
+            &bounds,
+            ((0.0, 0.0), 1.0),
+            &small,
+            ((10.0, 10.0), 0.1)
+        ));
+
+        let far = Aabb::from_points(&[(20.0, 20.0), (30.0, 30.0)]);
+        assert!(!are_bounds_overlapping(
+            &bounds,
+            circle,
+            &far,
+            ((25.0, 25.0), 7.1)
+        ));
+    }
+
     #[test]
     fn test_are_polygons_intersecting() {
         let mut poly_a: Vec<(f32, f32)> = vec![(0.0, 0.0), (0.0, 50.0), (50.0, 50.0), (50.0, 0.0)];
@@ -244,6 +1536,336 @@ mod tests {
         assert!(!result);
     }
 
+    #[test]
+    fn test_get_polygons_penetration() {
+        let poly_a = vec![(0.0, 0.0), (0.0, 10.0), (10.0, 10.0), (10.0, 0.0)];
+        let poly_b = vec![(8.0, 2.0), (8.0, 8.0), (20.0, 8.0), (20.0, 2.0)];
+        let result = get_polygons_penetration(poly_a.clone(), poly_b.clone()).unwrap();
+        assert_eq!(result.depth, 2.0);
+        assert_eq!(result.normal, (-1.0, 0.0));
+        assert_eq!(result.mtv, (-2.0, 0.0));
+
+        // Swapping the polygons flips the direction
+        let result = get_polygons_penetration(poly_b, poly_a.clone()).unwrap();
+        assert_eq!(result.mtv, (2.0, 0.0));
+
+        let far = vec![(50.0, 50.0), (50.0, 60.0), (60.0, 60.0)];
+        assert!(get_polygons_penetration(poly_a.clone(), far).is_none());
+        assert!(get_polygons_penetration(poly_a, vec![]).is_none());
+    }
+
+    #[test]
+    fn test_get_polygons_penetration_contained() {
+        let outer = vec![(0.0, 0.0), (0.0, 10.0), (10.0, 10.0), (10.0, 0.0)];
+        let inner = vec![(1.0, 4.0), (1.0, 6.0), (3.0, 6.0), (3.0, 4.0)];
+        let result = get_polygons_penetration(inner, outer).unwrap();
+        // Leaving through the nearest (left) side needs a move of 3
+        assert_eq!(result.depth, 3.0);
+        assert_eq!(result.mtv, (-3.0, 0.0));
+    }
+
+    #[test]
+    fn test_get_polygons_time_of_impact() {
+        let square = vec![(0.0, 0.0), (0.0, 2.0), (2.0, 2.0), (2.0, 0.0)];
+        let wall = vec![(10.0, -5.0), (10.0, 5.0), (11.0, 5.0), (11.0, -5.0)];
+
+        // Far enough in one step to tunnel straight through the wall
+        let result =
+            get_polygons_time_of_impact(square.clone(), (40.0, 0.0), wall.clone(), None).unwrap();
+        assert_eq!(result.time, 0.2);
+        assert_eq!(result.normal, (-1.0, 0.0));
+
+        // The same relative motion with the wall moving instead
+        let result = get_polygons_time_of_impact(
+            square.clone(),
+            (0.0, 0.0),
+            wall.clone(),
+            Some((-40.0, 0.0)),
+        )
+        .unwrap();
+        assert_eq!(result.time, 0.2);
+
+        // Stopping short, moving away, and passing by all miss
+        assert!(
+            get_polygons_time_of_impact(square.clone(), (7.0, 0.0), wall.clone(), None).is_none()
+        );
+        assert!(
+            get_polygons_time_of_impact(square.clone(), (-40.0, 0.0), wall.clone(), None).is_none()
+        );
+        assert!(
+            get_polygons_time_of_impact(square.clone(), (40.0, 40.0), wall.clone(), None).is_none()
+        );
+        assert!(get_polygons_time_of_impact(square.clone(), (1.0, 0.0), vec![], None).is_none());
+    }
+
+    #[test]
+    fn test_get_polygons_time_of_impact_touching() {
+        let square = vec![(0.0, 0.0), (0.0, 2.0), (2.0, 2.0), (2.0, 0.0)];
+        let floor = vec![(-10.0, -1.0), (-10.0, 0.0), (10.0, 0.0), (10.0, -1.0)];
+
+        // Sliding along the floor is not an impact, pushing into it is
+        assert!(
+            get_polygons_time_of_impact(square.clone(), (5.0, 0.0), floor.clone(), None).is_none()
+        );
+        let result =
+            get_polygons_time_of_impact(square.clone(), (5.0, -1.0), floor.clone(), None).unwrap();
+        assert_eq!(result.time, 0.0);
+        assert_eq!(result.normal, (0.0, 1.0));
+
+        // Overlapping shapes report the way out
+        let overlapping = vec![(1.0, 0.5), (1.0, 1.5), (5.0, 1.5), (5.0, 0.5)];
+        let result = get_polygons_time_of_impact(square, (0.0, 0.0), overlapping, None).unwrap();
+        assert_eq!(result.time, 0.0);
+        assert_eq!(result.normal, (-1.0, 0.0));
+    }
+
+    #[test]
+    fn test_cast_ray_against_polygon() {
+        let square = vec![(0.0, 0.0), (0.0, 2.0), (2.0, 2.0), (2.0, 0.0)];
+        let hit = cast_ray_against_polygon((-3.0, 1.0), (2.0, 0.0), square.clone(), None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(hit.point, (0.0, 1.0));
+        assert_eq!(hit.normal, (-1.0, 0.0));
+        assert_eq!(hit.distance, 3.0);
+
+        let hit = cast_ray_against_polygon((1.0, 5.0), (0.0, -1.0), square.clone(), None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(hit.point, (1.0, 2.0));
+        assert_eq!(hit.normal, (0.0, 1.0));
+
+        // Too short, pointing away and starting inside
+        let origin = (-3.0, 1.0);
+        assert!(
+            cast_ray_against_polygon(origin, (1.0, 0.0), square.clone(), Some(2.5))
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            cast_ray_against_polygon(origin, (-1.0, 0.0), square.clone(), None)
+                .unwrap()
+                .is_none()
+        );
+        let hit = cast_ray_against_polygon((1.0, 1.0), (0.0, 1.0), square.clone(), None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(hit.distance, 0.0);
+        assert_eq!(hit.normal, (0.0, -1.0));
+        assert!(cast_ray_against_polygon(origin, (0.0, 0.0), square, None).is_err());
+    }
+
+    #[test]
+    fn test_cast_ray_against_polygon_concave() {
+        // A U shape open at the top, with the ray passing down through the gap
+        let u_shape = vec![
+            (0.0, 0.0),
+            (3.0, 0.0),
+            (3.0, 3.0),
+            (2.0, 3.0),
+            (2.0, 1.0),
+            (1.0, 1.0),
+            (1.0, 3.0),
+            (0.0, 3.0),
+        ];
+        let hit =
+            cast_ray_against_polygon_native((1.5, 5.0), (0.0, -1.0), &u_shape, 100.0).unwrap();
+        assert_eq!(hit.point, (1.5, 1.0));
+        assert_eq!(hit.distance, 4.0);
+    }
+
+    #[test]
+    fn test_cast_ray_against_round_shapes() {
+        let hit =
+            cast_ray_against_circle_native((-5.0, 0.0), (1.0, 0.0), (0.0, 0.0), 2.0, 10.0).unwrap();
+        assert_eq!(hit.point, (-2.0, 0.0));
+        assert_eq!(hit.normal, (-1.0, 0.0));
+        assert!(
+            cast_ray_against_circle_native((-5.0, 3.0), (1.0, 0.0), (0.0, 0.0), 2.0, 10.0)
+                .is_none()
+        );
+        assert!(
+            cast_ray_against_circle_native((-5.0, 0.0), (-1.0, 0.0), (0.0, 0.0), 2.0, 10.0)
+                .is_none()
+        );
+
+        // Hitting the straight side and the rounded end of a horizontal capsule
+        let capsule = ((0.0, 0.0), (10.0, 0.0), 1.0);
+        let hit = cast_ray_against_capsule_native(
+            (5.0, 4.0),
+            (0.0, -1.0),
+            capsule.0,
+            capsule.1,
+            capsule.2,
+            10.0,
+        )
+        .unwrap();
+        assert_eq!(hit.point, (5.0, 1.0));
+        assert_eq!(hit.normal, (0.0, 1.0));
+        let hit = cast_ray_against_capsule_native(
+            (15.0, 0.0),
+            (-1.0, 0.0),
+            capsule.0,
+            capsule.1,
+            capsule.2,
+            10.0,
+        )
+        .unwrap();
+        assert_eq!(hit.point, (11.0, 0.0));
+        assert_eq!(hit.normal, (1.0, 0.0));
+    }
+
+    #[test]
+    fn test_aabb_is_hit_by_ray() {
+        let bounds = Aabb::from_points(&[(0.0, 0.0), (2.0, 2.0)]);
+        assert!(bounds.is_hit_by_ray((-3.0, 1.0), (1.0, 0.0), 10.0));
+        assert!(!bounds.is_hit_by_ray((-3.0, 1.0), (1.0, 0.0), 2.0));
+        assert!(!bounds.is_hit_by_ray((-3.0, 1.0), (-1.0, 0.0), 10.0));
+        assert!(!bounds.is_hit_by_ray((-3.0, 3.0), (1.0, 0.0), 10.0));
+        assert!(!Aabb::EMPTY.is_hit_by_ray((0.0, 0.0), (1.0, 0.0), 10.0));
+    }
+
+    #[test]
+    fn test_slide_vector() {
+        assert_eq!(slide_vector((3.0, -4.0), (0.0, 1.0)), (3.0, 0.0));
+        // Moving away from the surface is untouched
+        assert_eq!(slide_vector((3.0, 4.0), (0.0, 1.0)), (3.0, 4.0));
+        assert_eq!(slide_vector((3.0, -4.0), (0.0, 0.0)), (3.0, -4.0));
+    }
+
+    #[test]
+    fn test_reflect_vector() {
+        assert_eq!(reflect_vector((3.0, -4.0), (0.0, 2.0), None), (3.0, 4.0));
+        assert_eq!(
+            reflect_vector((3.0, -4.0), (0.0, 1.0), Some(0.5)),
+            (3.0, 2.0)
+        );
+        assert_eq!(reflect_vector((3.0, 4.0), (0.0, 1.0), None), (3.0, 4.0));
+    }
+
+    #[test]
+    fn test_get_point_segment_distance_squared() {
+        assert_eq!(
+            get_point_segment_distance_squared((5.0, 3.0), (0.0, 0.0), (10.0, 0.0)),
+            9.0
+        );
+        // Beyond the end of the segment the distance is to the end point
+        assert_eq!(
+            get_point_segment_distance_squared((13.0, 4.0), (0.0, 0.0), (10.0, 0.0)),
+            25.0
+        );
+        // Degenerate segment
+        assert_eq!(
+            get_point_segment_distance_squared((3.0, 4.0), (0.0, 0.0), (0.0, 0.0)),
+            25.0
+        );
+    }
+
+    #[test]
+    fn test_are_circles_intersecting() {
+        assert!(are_circles_intersecting((0.0, 0.0), 5.0, (8.0, 0.0), 4.0));
+        // Touching does not count, matching the polygon test
+        assert!(!are_circles_intersecting((0.0, 0.0), 5.0, (9.0, 0.0), 4.0));
+    }
+
+    #[test]
+    fn test_is_circle_intersecting_polygon() {
+        let square = vec![(0.0, 0.0), (0.0, 10.0), (10.0, 10.0), (10.0, 0.0)];
+        assert!(is_circle_intersecting_polygon(
+            (5.0, 5.0),
+            1.0,
+            square.clone()
+        ));
+        assert!(is_circle_intersecting_polygon(
+            (12.0, 5.0),
+            2.5,
+            square.clone()
+        ));
+        assert!(!is_circle_intersecting_polygon(
+            (12.0, 5.0),
+            1.5,
+            square.clone()
+        ));
+        // Near a corner the box test would pass but the circle misses
+        assert!(!is_circle_intersecting_polygon(
+            (12.0, 12.0),
+            2.5,
+            square.clone()
+        ));
+        assert!(!is_circle_intersecting_polygon((5.0, 5.0), 1.0, vec![]));
+    }
+
+    #[test]
+    fn test_get_segments_distance_squared() {
+        // Crossing
+        assert_eq!(
+            get_segments_distance_squared((0.0, 0.0), (2.0, 2.0), (0.0, 2.0), (2.0, 0.0)),
+            0.0
+        );
+        // Parallel
+        assert_eq!(
+            get_segments_distance_squared((0.0, 0.0), (10.0, 0.0), (0.0, 3.0), (10.0, 3.0)),
+            9.0
+        );
+        // T shape, closest at an end point
+        assert_eq!(
+            get_segments_distance_squared((0.0, 0.0), (10.0, 0.0), (5.0, 2.0), (5.0, 8.0)),
+            4.0
+        );
+    }
+
+    #[test]
+    fn test_are_capsules_intersecting() {
+        assert!(are_capsules_intersecting(
+            (0.0, 0.0),
+            (0.0, 10.0),
+            1.0,
+            (2.5, 5.0),
+            (2.5, 5.0),
+            2.0
+        ));
+        assert!(!are_capsules_intersecting(
+            (0.0, 0.0),
+            (0.0, 10.0),
+            1.0,
+            (2.5, 12.0),
+            (10.0, 12.0),
+            1.0
+        ));
+    }
+
+    #[test]
+    fn test_is_capsule_intersecting_polygon() {
+        let square = vec![(0.0, 0.0), (0.0, 10.0), (10.0, 10.0), (10.0, 0.0)];
+        // Segment through the middle of the square
+        assert!(is_capsule_intersecting_polygon(
+            (-5.0, 5.0),
+            (15.0, 5.0),
+            0.5,
+            square.clone()
+        ));
+        // Fully inside
+        assert!(is_capsule_intersecting_polygon(
+            (2.0, 5.0),
+            (8.0, 5.0),
+            0.5,
+            square.clone()
+        ));
+        // Running alongside the right edge
+        assert!(is_capsule_intersecting_polygon(
+            (11.0, 0.0),
+            (11.0, 10.0),
+            1.5,
+            square.clone()
+        ));
+        assert!(!is_capsule_intersecting_polygon(
+            (11.0, 0.0),
+            (11.0, 10.0),
+            0.5,
+            square
+        ));
+    }
+
     #[test]
     fn test_is_point_in_box() {
         // point inside
@@ -292,6 +1914,26 @@ mod tests {
         assert!(!result);
     }
 
+    #[test]
+    fn test_get_segments_intersection_point() {
+        assert_eq!(
+            get_segments_intersection_point((0.0, 0.0), (2.0, 2.0), (0.0, 2.0), (2.0, 0.0)),
+            Some((1.0, 1.0))
+        );
+        assert_eq!(
+            get_segments_intersection_point((0.0, 0.0), (1.0, 0.0), (1.0, 0.0), (1.0, 1.0)),
+            Some((1.0, 0.0))
+        );
+        assert_eq!(
+            get_segments_intersection_point((0.0, 0.0), (1.0, 1.0), (0.0, 2.0), (2.0, 2.0)),
+            None
+        );
+        assert_eq!(
+            get_segments_intersection_point((0.0, 0.0), (0.0, 1.0), (1.0, 0.0), (1.0, 1.0)),
+            None
+        );
+    }
+
     #[test]
     fn test_point_in_rectangle() {
         let polygon = vec![(0.0, 0.0), (0.0, 50.0), (50.0, 50.0), (50.0, 0.0)];
@@ -312,4 +1954,121 @@ mod tests {
         let result = is_point_in_polygon(25.0, 25.0, polygon);
         assert!(!result);
     }
+
+    fn is_path_simple(points: &[(f32, f32)], closed: bool) -> bool {
+        let indices: Vec<usize> = (0..points.len()).collect();
+        let mut edges = get_path_edges(&indices, closed);
+        edges.all(|(a, b)| !is_segment_crossing_path(points, &indices, closed, a, b))
+    }
+
+    #[test]
+    fn test_simplify_polyline_rdp() {
+        let line = vec![
+            (0.0, 0.0),
+            (1.0, 0.1),
+            (2.0, -0.1),
+            (3.0, 5.0),
+            (4.0, 6.0),
+            (5.0, 7.0),
+        ];
+        assert_eq!(
+            simplify_polyline_rdp(line.clone(), 1.0),
+            vec![(0.0, 0.0), (2.0, -0.1), (3.0, 5.0), (5.0, 7.0)]
+        );
+        assert_eq!(
+            simplify_polyline_rdp(line.clone(), 100.0),
+            vec![(0.0, 0.0), (5.0, 7.0)]
+        );
+        assert_eq!(
+            simplify_polyline_rdp(line.clone(), 0.0),
+            vec![(0.0, 0.0), (1.0, 0.1), (2.0, -0.1), (3.0, 5.0), (5.0, 7.0)]
+        );
+    }
+
+    #[test]
+    fn test_simplify_polygon_rdp() {
+        // A square with noisy points along its edges, starting mid-edge
+        let polygon = vec![
+            (5.0, 0.0),
+            (10.0, 0.0),
+            (10.0, 3.0),
+            (10.1, 6.0),
+            (10.0, 10.0),
+            (0.0, 10.0),
+            (0.1, 5.0),
+            (0.0, 0.0),
+            (2.0, 0.1),
+        ];
+        let simplified = simplify_polygon_rdp(polygon, 0.5);
+        assert_eq!(
+            simplified,
+            vec![(10.0, 0.0), (10.0, 10.0), (0.0, 10.0), (0.0, 0.0)]
+        );
+        // Never fewer than three points
+        let simplified =
+            simplify_polygon_rdp(vec![(0.0, 0.0), (1.0, 0.0), (2.0, 0.0), (1.0, 0.1)], 10.0);
+        assert_eq!(simplified.len(), 3);
+    }
+
+    #[test]
+    fn test_simplify_polygon_stays_simple() {
+        // Plain RDP drops (0, -1), and the shortcut past it would cross the first edge
+        let polygon = vec![
+            (10.0, 1.0),
+            (-1.0, 1.0),
+            (-7.0, 6.0),
+            (0.0, -1.0),
+            (7.0, -5.0),
+        ];
+        let simplified = simplify_polygon_rdp(polygon.clone(), 2.0);
+        assert!(simplified.contains(&(0.0, -1.0)));
+        assert!(is_path_simple(&simplified, true));
+
+        let simplified = simplify_polygon_vw(polygon, 100.0);
+        assert!(is_path_simple(&simplified, true));
+    }
+
+    #[test]
+    fn test_simplify_vw() {
+        let polygon = vec![
+            (0.0, 0.0),
+            (5.0, 0.2),
+            (10.0, 0.0),
+            (10.0, 10.0),
+            (0.0, 10.0),
+        ];
+        assert_eq!(
+            simplify_polygon_vw(polygon.clone(), 1.5),
+            vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)]
+        );
+        assert_eq!(simplify_polygon_vw(polygon.clone(), 0.5), polygon);
+        assert_eq!(simplify_polygon_vw(polygon, 1000.0).len(), 3);
+
+        let line = vec![(0.0, 0.0), (1.0, 0.1), (2.0, 0.0), (3.0, 3.0)];
+        assert_eq!(
+            simplify_polyline_vw(line, 0.5),
+            vec![(0.0, 0.0), (2.0, 0.0), (3.0, 3.0)]
+        );
+    }
+
+    #[test]
+    fn test_remove_collinear_points() {
+        let polygon = vec![
+            (0.0, 0.0),
+            (5.0, 0.0),
+            (10.0, 0.0),
+            (10.0, 10.0),
+            (10.0, 10.0),
+            (0.0, 10.0),
+            (0.0, 5.0),
+            (0.0, 0.0),
+        ];
+        assert_eq!(
+            remove_collinear_points(polygon, None),
+            vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)]
+        );
+        let nearly = vec![(0.0, 0.0), (5.0, 0.01), (10.0, 0.0), (5.0, 5.0)];
+        assert_eq!(remove_collinear_points(nearly.clone(), None), nearly);
+        assert_eq!(remove_collinear_points(nearly, Some(0.1)).len(), 3);
+    }
 }
@@ -1,74 +1,1020 @@
+use pyo3::exceptions::{PyKeyError, PyTypeError, PyValueError};
+use pyo3::intern;
 use pyo3::prelude::*;
+use pyo3::pyclass::boolean_struct::False;
+use pyo3::types::{PyDict, PyType};
+use pyo3::PyClass;
+
+use crate::geometry::{get_bounding_radius, get_convex_hull_native, Aabb};
+use crate::point_set::{
+    adjusted_points_memoryview, extract_shared_points, extract_values, points_memoryview,
+    refill_shared_points, PointSet, SharedAdjustedPoints, SharedPoints,
+};
+use crate::shape::Shape;
 
 pub trait NativeAdjustedPoints {
     fn get_adjusted_points_native(&mut self) -> &Vec<(f32, f32)>;
+    fn get_bounds_native(&mut self) -> Aabb;
+    /// The hit box position and the distance from it to the farthest adjusted point.
+    fn get_bounding_circle_native(&mut self) -> ((f32, f32), f32);
+    /// The exact geometry to run collision tests against.
+    fn get_shape_native(&mut self) -> Shape<'_> {
+        Shape::Polygon(self.get_adjusted_points_native())
+    }
+}
+
+/// Any other hit box object, read once through its Python `get_adjusted_points()`.
+pub struct PythonHitBox {
+    pub adjusted_points: Vec<(f32, f32)>,
+    pub bounds: Aabb,
+    pub center: (f32, f32),
+    pub radius: f32,
+}
+
+impl PythonHitBox {
+    pub fn new(adjusted_points: Vec<(f32, f32)>) -> PythonHitBox {
+        let bounds = Aabb::from_points(&adjusted_points);
+        let center = if bounds.is_empty() {
+            (0.0, 0.0)
+        } else {
+            (
+                (bounds.left + bounds.right) / 2.0,
+                (bounds.bottom + bounds.top) / 2.0,
+            )
+        };
+        let radius = get_bounding_radius(&adjusted_points, center);
+        PythonHitBox {
+            adjusted_points,
+            bounds,
+            center,
+            radius,
+        }
+    }
+}
+
+impl NativeAdjustedPoints for PythonHitBox {
+    fn get_adjusted_points_native(&mut self) -> &Vec<(f32, f32)> {
+        &self.adjusted_points
+    }
+
+    fn get_bounds_native(&mut self) -> Aabb {
+        self.bounds
+    }
+
+    fn get_bounding_circle_native(&mut self) -> ((f32, f32), f32) {
+        (self.center, self.radius)
+    }
+}
+
+/// A hit box pulled out of a sprite for collision checks.
+///
+/// Native hit boxes are borrowed from their Python objects, so the adjusted points
+/// they cache while being checked are kept for the next check.
+pub enum ExtractedHitBox<'py> {
+    HitBox(PyRefMut<'py, HitBox>),
+    Rotatable(PyRefMut<'py, RotatableHitBox>),
+    Circle(PyRefMut<'py, CircleHitBox>),
+    Capsule(PyRefMut<'py, CapsuleHitBox>),
+    Compound(PyRefMut<'py, CompoundHitBox>),
+    Python(PythonHitBox),
+}
+
+impl<'py> ExtractedHitBox<'py> {
+    /// Native hit boxes are used directly, anything else providing `get_adjusted_points()`
+    /// is called through Python, and other objects raise `TypeError`. Python subclasses of
+    /// the native types go through Python too, since they may override the method.
+    ///
+    /// Borrowing a native hit box that is already borrowed, such as the same object
+    /// extracted twice, raises `RuntimeError`.
+    pub fn from_hit_box(
+        py: Python<'py>,
+        hitbox_py_object: &'py PyAny,
+    ) -> PyResult<ExtractedHitBox<'py>> {
+        if hitbox_py_object.is_exact_instance_of::<HitBox>() {
+            Ok(ExtractedHitBox::HitBox(hitbox_py_object.extract()?))
+        } else if hitbox_py_object.is_exact_instance_of::<RotatableHitBox>() {
+            Ok(ExtractedHitBox::Rotatable(hitbox_py_object.extract()?))
+        } else if hitbox_py_object.is_exact_instance_of::<CircleHitBox>() {
+            Ok(ExtractedHitBox::Circle(hitbox_py_object.extract()?))
+        } else if hitbox_py_object.is_exact_instance_of::<CapsuleHitBox>() {
+            Ok(ExtractedHitBox::Capsule(hitbox_py_object.extract()?))
+        } else if hitbox_py_object.is_exact_instance_of::<CompoundHitBox>() {
+            Ok(ExtractedHitBox::Compound(hitbox_py_object.extract()?))
+        } else if hitbox_py_object.hasattr(intern!(py, "get_adjusted_points"))? {
+            let points: Vec<(f32, f32)> = hitbox_py_object
+                .call_method0(intern!(py, "get_adjusted_points"))?
+                .extract()?;
+            Ok(ExtractedHitBox::Python(PythonHitBox::new(points)))
+        } else {
+            Err(PyTypeError::new_err(format!(
+                "Unknown hit box type '{}', expected an object with get_adjusted_points()",
+                hitbox_py_object.get_type().name()?
+            )))
+        }
+    }
+
+    pub fn native(&mut self) -> &mut dyn NativeAdjustedPoints {
+        match self {
+            ExtractedHitBox::HitBox(hitbox) => &mut **hitbox,
+            ExtractedHitBox::Rotatable(hitbox) => &mut **hitbox,
+            ExtractedHitBox::Circle(hitbox) => &mut **hitbox,
+            ExtractedHitBox::Capsule(hitbox) => &mut **hitbox,
+            ExtractedHitBox::Compound(hitbox) => &mut **hitbox,
+            ExtractedHitBox::Python(hitbox) => hitbox,
+        }
+    }
+
+    /// The exact geometry to run collision tests against.
+    pub fn shape(&mut self) -> Shape<'_> {
+        self.native().get_shape_native()
+    }
+
+    /// The hit box as convex polygons: the parts of a compound hit box, or the adjusted
+    /// points of any other, so circles and capsules give their polygon outline.
+    pub fn polygons(&mut self) -> Vec<&[(f32, f32)]> {
+        match self {
+            ExtractedHitBox::Compound(hitbox) => hitbox
+                .get_adjusted_parts_native()
+                .iter()
+                .map(Vec::as_slice)
+                .collect(),
+            hitbox => vec![hitbox.native().get_adjusted_points_native().as_slice()],
+        }
+    }
+}
+
+/// Rotate, scale and then move points the same way `RotatableHitBox` does.
+pub fn get_rotated_points(
+    points: &[(f32, f32)],
+    position: (f32, f32),
+    scale: (f32, f32),
+    angle: f32,
+) -> Vec<(f32, f32)> {
+    rotate_points(points, position, scale, angle).collect()
+}
+
+/// Lazily rotate, scale and then move points, see `get_rotated_points`.
+fn rotate_points(
+    points: &[(f32, f32)],
+    position: (f32, f32),
+    scale: (f32, f32),
+    angle: f32,
+) -> impl Iterator<Item = (f32, f32)> + '_ {
+    let rad = angle.to_radians();
+    let rad_cos = rad.cos();
+    let rad_sin = rad.sin();
+    points.iter().map(move |point| {
+        let x = ((point.0 * rad_cos + point.1 * rad_sin) * scale.0) + position.0;
+        let y = ((-point.0 * rad_sin + point.1 * rad_cos) * scale.1) + position.1;
+        (x, y)
+    })
+}
+
+/// The `__new__` of the native hit box class, which builds instances of Python subclasses
+/// without calling their own `__new__` or `__init__`.
+fn get_native_new<T: PyClass>(py: Python<'_>) -> PyResult<&PyAny> {
+    T::type_object(py).getattr(intern!(py, "__new__"))
+}
+
+/// Copy a hit box by creating an instance of its class with the native `__new__` and
+/// `points`, then cloning the Rust state into it, so cached points carry over and Python
+/// subclasses keep their type and attributes without their `__init__` running again.
+/// The attributes are deep copied when a `memo` is given, after the copy is added to it
+/// so attributes referring back to the hit box get the copy.
+fn copy_hit_box<'py, T>(
+    slf: &'py PyCell<T>,
+    points: PointSet,
+    memo: Option<&'py PyAny>,
+) -> PyResult<&'py PyAny>
+where
+    T: PyClass<Frozen = False> + Clone,
+{
+    let py = slf.py();
+    let copy = get_native_new::<T>(py)?.call1((slf.get_type(), points))?;
+    *copy.downcast::<PyCell<T>>()?.borrow_mut() = slf.borrow().clone();
+    if let Ok(attributes) = slf.getattr(intern!(py, "__dict__")) {
+        let attributes = match memo {
+            Some(memo) => {
+                memo.set_item(slf.as_ptr() as usize, copy)?;
+                py.import(intern!(py, "copy"))?
+                    .call_method1(intern!(py, "deepcopy"), (attributes, memo))?
+            }
+            None => attributes.call_method0(intern!(py, "copy"))?,
+        };
+        copy.getattr(intern!(py, "__dict__"))?
+            .call_method1(intern!(py, "update"), (attributes,))?;
+    }
+    Ok(copy)
+}
+
+/// Attributes a Python subclass added to a hit box, for the state part of `__reduce__`.
+fn get_instance_attributes<T: PyClass>(slf: &PyCell<T>) -> Option<&PyAny> {
+    slf.getattr(intern!(slf.py(), "__dict__")).ok()
+}
+
+fn get_dict_item<'py>(data: &'py PyDict, key: &str) -> PyResult<&'py PyAny> {
+    data.get_item(key)?
+        .ok_or_else(|| PyKeyError::new_err(key.to_string()))
+}
+
+/// Points from a `to_dict` result, accepting the lists JSON turns tuples into.
+fn get_dict_points(data: &PyDict) -> PyResult<Vec<(f32, f32)>> {
+    let points: Vec<[f32; 2]> = get_dict_item(data, "points")?.extract()?;
+    Ok(points.into_iter().map(|[x, y]| (x, y)).collect())
+}
+
+fn get_dict_pair(data: &PyDict, key: &str) -> PyResult<Option<(f32, f32)>> {
+    match data.get_item(key)? {
+        Some(value) => {
+            let [x, y]: [f32; 2] = value.extract()?;
+            Ok(Some((x, y)))
+        }
+        None => Ok(None),
+    }
+}
+
+#[derive(Clone)]
+#[pyclass(subclass, module = "arcade.hitbox.base")]
+pub struct HitBox {
+    pub points: SharedPoints,
+    #[pyo3(get)]
+    pub position: (f32, f32),
+    #[pyo3(get)]
+    pub scale: (f32, f32),
+    pub angle: f32,
+
+    pub adjusted_cache: SharedAdjustedPoints,
+    pub bounds_cache: Aabb,
+    pub radius_cache: f32,
+    pub cache_dirty: bool,
+}
+
+#[pymethods]
+impl HitBox {
+    #[new]
+    fn new(
+        points: &PyAny,
+        position: Option<(f32, f32)>,
+        scale: Option<(f32, f32)>,
+    ) -> PyResult<HitBox> {
+        Ok(HitBox::new_native(
+            extract_shared_points(points)?,
+            position,
+            scale,
+        ))
+    }
+
+    fn create_rotatable(
+        self_: PyRef<'_, Self>,
+        py: Python<'_>,
+        angle: Option<f32>,
+    ) -> PyResult<Py<RotatableHitBox>> {
+        let adjustable: Py<RotatableHitBox> = Py::new(
+            py,
+            RotatableHitBox::new_native(
+                self_.points.clone(),
+                Some(self_.position),
+                Some(self_.scale),
+                angle,
+            ),
+        )
+        .unwrap();
+        Ok(adjustable)
+    }
+
+    pub fn get_adjusted_points(&mut self) -> Vec<(f32, f32)> {
+        self.get_adjusted_points_native().to_vec()
+    }
+
+    /// The adjusted points as a read-only float32 N×2 memoryview, without copying.
+    pub fn get_adjusted_points_buffer<'py>(&mut self, py: Python<'py>) -> PyResult<&'py PyAny> {
+        self.update_cache();
+        adjusted_points_memoryview(py, self.adjusted_cache.clone())
+    }
+
+    /// The points as a read-only float32 N×2 memoryview, without copying.
+    #[getter]
+    pub fn points_buffer<'py>(&self, py: Python<'py>) -> PyResult<&'py PyAny> {
+        points_memoryview(py, self.points.clone())
+    }
+
+    /// Pickle through the native `__new__`, so subclasses with their own constructor
+    /// arguments can be unpickled too.
+    #[allow(clippy::type_complexity)]
+    pub fn __reduce__(
+        slf: &PyCell<Self>,
+    ) -> PyResult<(
+        &PyAny,
+        (&PyType, Vec<(f32, f32)>, (f32, f32), (f32, f32)),
+        Option<&PyAny>,
+    )> {
+        let hit_box = slf.borrow();
+        Ok((
+            get_native_new::<Self>(slf.py())?,
+            (
+                slf.get_type(),
+                hit_box.points.to_vec(),
+                hit_box.position,
+                hit_box.scale,
+            ),
+            get_instance_attributes(slf),
+        ))
+    }
+
+    pub fn __copy__(slf: &PyCell<Self>) -> PyResult<&PyAny> {
+        let points = PointSet::from(slf.borrow().points.clone());
+        copy_hit_box(slf, points, None)
+    }
+
+    pub fn __deepcopy__<'py>(slf: &'py PyCell<Self>, memo: &'py PyAny) -> PyResult<&'py PyAny> {
+        let points = PointSet::from(slf.borrow().points.clone());
+        copy_hit_box(slf, points, Some(memo))
+    }
+
+    /// The points, position and scale as a dictionary that can be saved as JSON.
+    pub fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<&'py PyDict> {
+        let data = PyDict::new(py);
+        data.set_item("points", self.points.to_vec())?;
+        data.set_item("position", self.position)?;
+        data.set_item("scale", self.scale)?;
+        Ok(data)
+    }
+
+    #[classmethod]
+    pub fn from_dict<'py>(cls: &'py PyType, data: &'py PyDict) -> PyResult<&'py PyAny> {
+        cls.call1((
+            get_dict_points(data)?,
+            get_dict_pair(data, "position")?,
+            get_dict_pair(data, "scale")?,
+        ))
+    }
+
+    #[getter]
+    pub fn points(&self) -> Vec<(f32, f32)> {
+        self.points.to_vec()
+    }
+
+    /// The points as a `PointSet` sharing this hit box's data.
+    #[getter]
+    pub fn point_set(&self) -> PointSet {
+        PointSet::from(self.points.clone())
+    }
+
+    #[setter]
+    pub fn set_points(&mut self, value: &PyAny) -> PyResult<()> {
+        self.set_points_native(extract_shared_points(value)?);
+        Ok(())
+    }
+
+    #[setter]
+    pub fn set_position(&mut self, value: (f32, f32)) -> PyResult<()> {
+        self.position = value;
+        self.cache_dirty = true;
+        Ok(())
+    }
+
+    #[setter]
+    pub fn set_scale(&mut self, value: (f32, f32)) -> PyResult<()> {
+        self.scale = value;
+        self.cache_dirty = true;
+        Ok(())
+    }
+
+    #[getter]
+    pub fn left(&mut self) -> PyResult<f32> {
+        Ok(self.get_bounds_native().left)
+    }
+
+    #[getter]
+    pub fn right(&mut self) -> PyResult<f32> {
+        Ok(self.get_bounds_native().right)
+    }
+
+    #[getter]
+    pub fn bottom(&mut self) -> PyResult<f32> {
+        Ok(self.get_bounds_native().bottom)
+    }
+
+    #[getter]
+    pub fn top(&mut self) -> PyResult<f32> {
+        Ok(self.get_bounds_native().top)
+    }
+
+    /// The adjusted points' extent as `(left, right, bottom, top)`.
+    #[getter]
+    pub fn bounds(&mut self) -> (f32, f32, f32, f32) {
+        let bounds = self.get_bounds_native();
+        (bounds.left, bounds.right, bounds.bottom, bounds.top)
+    }
+}
+
+impl HitBox {
+    pub fn new_native(
+        points: impl Into<SharedPoints>,
+        position: Option<(f32, f32)>,
+        scale: Option<(f32, f32)>,
+    ) -> HitBox {
+        let final_position = position.unwrap_or((0.0, 0.0));
+        let final_scale = scale.unwrap_or((1.0, 1.0));
+        HitBox {
+            points: points.into(),
+            position: final_position,
+            scale: final_scale,
+            angle: 0.0,
+            adjusted_cache: SharedAdjustedPoints::default(),
+            bounds_cache: Aabb::EMPTY,
+            radius_cache: 0.0,
+            cache_dirty: true,
+        }
+    }
+
+    pub fn set_points_native(&mut self, points: impl Into<SharedPoints>) {
+        self.points = points.into();
+        self.cache_dirty = true;
+    }
+
+    fn update_cache(&mut self) {
+        if self.cache_dirty {
+            let (position, scale) = (self.position, self.scale);
+            refill_shared_points(
+                &mut self.adjusted_cache,
+                self.points.iter().map(|point| {
+                    let x = (point.0 * scale.0) + position.0;
+                    let y = (point.1 * scale.1) + position.1;
+                    (x, y)
+                }),
+            );
+            self.bounds_cache = Aabb::from_points(&self.adjusted_cache);
+            self.radius_cache = get_bounding_radius(&self.adjusted_cache, self.position);
+            self.cache_dirty = false;
+        }
+    }
+}
+
+impl NativeAdjustedPoints for HitBox {
+    fn get_adjusted_points_native(&mut self) -> &Vec<(f32, f32)> {
+        self.update_cache();
+        &self.adjusted_cache
+    }
+
+    fn get_bounds_native(&mut self) -> Aabb {
+        self.update_cache();
+        self.bounds_cache
+    }
+
+    fn get_bounding_circle_native(&mut self) -> ((f32, f32), f32) {
+        self.update_cache();
+        (self.position, self.radius_cache)
+    }
+}
+
+#[derive(Clone)]
+#[pyclass(subclass, module = "arcade.hitbox.base")]
+pub struct RotatableHitBox {
+    pub points: SharedPoints,
+    #[pyo3(get)]
+    pub position: (f32, f32),
+    #[pyo3(get)]
+    pub scale: (f32, f32),
+    #[pyo3(get)]
+    pub angle: f32,
+
+    pub adjusted_cache: SharedAdjustedPoints,
+    pub bounds_cache: Aabb,
+    pub radius_cache: f32,
+    pub cache_dirty: bool,
+}
+
+#[pymethods]
+impl RotatableHitBox {
+    #[new]
+    fn new(
+        points: &PyAny,
+        position: Option<(f32, f32)>,
+        scale: Option<(f32, f32)>,
+        angle: Option<f32>,
+    ) -> PyResult<RotatableHitBox> {
+        Ok(RotatableHitBox::new_native(
+            extract_shared_points(points)?,
+            position,
+            scale,
+            angle,
+        ))
+    }
+
+    fn create_rotatable(
+        self_: PyRef<'_, Self>,
+        py: Python<'_>,
+        angle: Option<f32>,
+    ) -> PyResult<Py<RotatableHitBox>> {
+        let adjustable: Py<RotatableHitBox> = Py::new(
+            py,
+            RotatableHitBox::new_native(
+                self_.points.clone(),
+                Some(self_.position),
+                Some(self_.scale),
+                angle,
+            ),
+        )
+        .unwrap();
+        Ok(adjustable)
+    }
+
+    pub fn get_adjusted_points(&mut self) -> Vec<(f32, f32)> {
+        self.get_adjusted_points_native().to_vec()
+    }
+
+    /// The adjusted points as a read-only float32 N×2 memoryview, without copying.
+    pub fn get_adjusted_points_buffer<'py>(&mut self, py: Python<'py>) -> PyResult<&'py PyAny> {
+        self.update_cache();
+        adjusted_points_memoryview(py, self.adjusted_cache.clone())
+    }
+
+    /// The points as a read-only float32 N×2 memoryview, without copying.
+    #[getter]
+    pub fn points_buffer<'py>(&self, py: Python<'py>) -> PyResult<&'py PyAny> {
+        points_memoryview(py, self.points.clone())
+    }
+
+    /// Pickle through the native `__new__`, so subclasses with their own constructor
+    /// arguments can be unpickled too.
+    #[allow(clippy::type_complexity)]
+    pub fn __reduce__(
+        slf: &PyCell<Self>,
+    ) -> PyResult<(
+        &PyAny,
+        (&PyType, Vec<(f32, f32)>, (f32, f32), (f32, f32), f32),
+        Option<&PyAny>,
+    )> {
+        let hit_box = slf.borrow();
+        Ok((
+            get_native_new::<Self>(slf.py())?,
+            (
+                slf.get_type(),
+                hit_box.points.to_vec(),
+                hit_box.position,
+                hit_box.scale,
+                hit_box.angle,
+            ),
+            get_instance_attributes(slf),
+        ))
+    }
+
+    pub fn __copy__(slf: &PyCell<Self>) -> PyResult<&PyAny> {
+        let points = PointSet::from(slf.borrow().points.clone());
+        copy_hit_box(slf, points, None)
+    }
+
+    pub fn __deepcopy__<'py>(slf: &'py PyCell<Self>, memo: &'py PyAny) -> PyResult<&'py PyAny> {
+        let points = PointSet::from(slf.borrow().points.clone());
+        copy_hit_box(slf, points, Some(memo))
+    }
+
+    /// The points, position, scale and angle as a dictionary that can be saved as JSON.
+    pub fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<&'py PyDict> {
+        let data = PyDict::new(py);
+        data.set_item("points", self.points.to_vec())?;
+        data.set_item("position", self.position)?;
+        data.set_item("scale", self.scale)?;
+        data.set_item("angle", self.angle)?;
+        Ok(data)
+    }
+
+    #[classmethod]
+    pub fn from_dict<'py>(cls: &'py PyType, data: &'py PyDict) -> PyResult<&'py PyAny> {
+        cls.call1((
+            get_dict_points(data)?,
+            get_dict_pair(data, "position")?,
+            get_dict_pair(data, "scale")?,
+            data.get_item("angle")?
+                .map(|angle| angle.extract::<f32>())
+                .transpose()?,
+        ))
+    }
+
+    #[getter]
+    pub fn points(&self) -> Vec<(f32, f32)> {
+        self.points.to_vec()
+    }
+
+    /// The points as a `PointSet` sharing this hit box's data.
+    #[getter]
+    pub fn point_set(&self) -> PointSet {
+        PointSet::from(self.points.clone())
+    }
+
+    #[setter]
+    pub fn set_points(&mut self, value: &PyAny) -> PyResult<()> {
+        self.set_points_native(extract_shared_points(value)?);
+        Ok(())
+    }
+
+    #[setter]
+    pub fn set_position(&mut self, value: (f32, f32)) -> PyResult<()> {
+        self.position = value;
+        self.cache_dirty = true;
+        Ok(())
+    }
+
+    #[setter]
+    pub fn set_scale(&mut self, value: (f32, f32)) -> PyResult<()> {
+        self.scale = value;
+        self.cache_dirty = true;
+        Ok(())
+    }
+
+    #[setter]
+    pub fn set_angle(&mut self, value: f32) -> PyResult<()> {
+        self.angle = value;
+        self.cache_dirty = true;
+        Ok(())
+    }
+
+    #[getter]
+    pub fn left(&mut self) -> PyResult<f32> {
+        Ok(self.get_bounds_native().left)
+    }
+
+    #[getter]
+    pub fn right(&mut self) -> PyResult<f32> {
+        Ok(self.get_bounds_native().right)
+    }
+
+    #[getter]
+    pub fn bottom(&mut self) -> PyResult<f32> {
+        Ok(self.get_bounds_native().bottom)
+    }
+
+    #[getter]
+    pub fn top(&mut self) -> PyResult<f32> {
+        Ok(self.get_bounds_native().top)
+    }
+
+    /// The adjusted points' extent as `(left, right, bottom, top)`.
+    #[getter]
+    pub fn bounds(&mut self) -> (f32, f32, f32, f32) {
+        let bounds = self.get_bounds_native();
+        (bounds.left, bounds.right, bounds.bottom, bounds.top)
+    }
+}
+
+impl RotatableHitBox {
+    pub fn new_native(
+        points: impl Into<SharedPoints>,
+        position: Option<(f32, f32)>,
+        scale: Option<(f32, f32)>,
+        angle: Option<f32>,
+    ) -> RotatableHitBox {
+        let final_position = position.unwrap_or((0.0, 0.0));
+        let final_scale = scale.unwrap_or((1.0, 1.0));
+        let final_angle = angle.unwrap_or(0.0);
+        RotatableHitBox {
+            points: points.into(),
+            position: final_position,
+            scale: final_scale,
+            angle: final_angle,
+            adjusted_cache: SharedAdjustedPoints::default(),
+            bounds_cache: Aabb::EMPTY,
+            radius_cache: 0.0,
+            cache_dirty: true,
+        }
+    }
+
+    pub fn set_points_native(&mut self, points: impl Into<SharedPoints>) {
+        self.points = points.into();
+        self.cache_dirty = true;
+    }
+
+    fn update_cache(&mut self) {
+        if self.cache_dirty {
+            refill_shared_points(
+                &mut self.adjusted_cache,
+                rotate_points(&self.points, self.position, self.scale, self.angle),
+            );
+            self.bounds_cache = Aabb::from_points(&self.adjusted_cache);
+            self.radius_cache = get_bounding_radius(&self.adjusted_cache, self.position);
+            self.cache_dirty = false;
+        }
+    }
+}
+
+impl NativeAdjustedPoints for RotatableHitBox {
+    fn get_adjusted_points_native(&mut self) -> &Vec<(f32, f32)> {
+        self.update_cache();
+        &self.adjusted_cache
+    }
+
+    fn get_bounds_native(&mut self) -> Aabb {
+        self.update_cache();
+        self.bounds_cache
+    }
+
+    fn get_bounding_circle_native(&mut self) -> ((f32, f32), f32) {
+        self.update_cache();
+        (self.position, self.radius_cache)
+    }
+}
+
+/// Points approximating a circle, counter-clockwise starting from the positive x axis.
+pub fn get_circle_points(center: (f32, f32), radius: f32, segments: usize) -> Vec<(f32, f32)> {
+    let step = std::f32::consts::TAU / segments as f32;
+    (0..segments)
+        .map(|i| {
+            let angle = step * i as f32;
+            (
+                center.0 + radius * angle.cos(),
+                center.1 + radius * angle.sin(),
+            )
+        })
+        .collect()
+}
+
+/// A round hit box, collided exactly rather than through its points.
+///
+/// Non-uniform scales use the larger axis so the circle stays round. The angle is
+/// stored for compatibility with sprites but does not affect the shape.
+#[derive(Clone)]
+#[pyclass(subclass)]
+pub struct CircleHitBox {
+    #[pyo3(get)]
+    pub radius: f32,
+    #[pyo3(get)]
+    pub position: (f32, f32),
+    #[pyo3(get)]
+    pub scale: (f32, f32),
+    #[pyo3(get, set)]
+    pub angle: f32,
+    /// Number of points used when the circle is read as a polygon
+    #[pyo3(get)]
+    pub segments: usize,
+
+    pub adjusted_cache: Vec<(f32, f32)>,
+    pub cache_dirty: bool,
+}
+
+#[pymethods]
+impl CircleHitBox {
+    #[new]
+    fn new(
+        radius: f32,
+        position: Option<(f32, f32)>,
+        scale: Option<(f32, f32)>,
+        segments: Option<usize>,
+    ) -> CircleHitBox {
+        let final_position = position.unwrap_or((0.0, 0.0));
+        let final_scale = scale.unwrap_or((1.0, 1.0));
+        let final_segments = segments.unwrap_or(32).max(3);
+        CircleHitBox {
+            radius,
+            position: final_position,
+            scale: final_scale,
+            angle: 0.0,
+            segments: final_segments,
+            adjusted_cache: vec![],
+            cache_dirty: true,
+        }
+    }
+
+    fn create_rotatable(&self, angle: Option<f32>) -> CircleHitBox {
+        let mut rotatable = self.clone();
+        rotatable.angle = angle.unwrap_or(0.0);
+        rotatable
+    }
+
+    #[getter]
+    pub fn points(&self) -> Vec<(f32, f32)> {
+        get_circle_points((0.0, 0.0), self.radius, self.segments)
+    }
+
+    #[getter]
+    pub fn adjusted_radius(&self) -> f32 {
+        self.radius * self.scale.0.abs().max(self.scale.1.abs())
+    }
+
+    pub fn get_adjusted_points(&mut self) -> Vec<(f32, f32)> {
+        self.get_adjusted_points_native().to_vec()
+    }
+
+    #[setter]
+    pub fn set_radius(&mut self, value: f32) -> PyResult<()> {
+        self.radius = value;
+        self.cache_dirty = true;
+        Ok(())
+    }
+
+    #[setter]
+    pub fn set_position(&mut self, value: (f32, f32)) -> PyResult<()> {
+        self.position = value;
+        self.cache_dirty = true;
+        Ok(())
+    }
+
+    #[setter]
+    pub fn set_scale(&mut self, value: (f32, f32)) -> PyResult<()> {
+        self.scale = value;
+        self.cache_dirty = true;
+        Ok(())
+    }
+
+    #[getter]
+    pub fn left(&self) -> PyResult<f32> {
+        Ok(self.position.0 - self.adjusted_radius())
+    }
+
+    #[getter]
+    pub fn right(&self) -> PyResult<f32> {
+        Ok(self.position.0 + self.adjusted_radius())
+    }
+
+    #[getter]
+    pub fn bottom(&self) -> PyResult<f32> {
+        Ok(self.position.1 - self.adjusted_radius())
+    }
+
+    #[getter]
+    pub fn top(&self) -> PyResult<f32> {
+        Ok(self.position.1 + self.adjusted_radius())
+    }
+
+    /// The circle's extent as `(left, right, bottom, top)`.
+    #[getter]
+    pub fn bounds(&mut self) -> (f32, f32, f32, f32) {
+        let bounds = self.get_bounds_native();
+        (bounds.left, bounds.right, bounds.bottom, bounds.top)
+    }
+}
+
+impl NativeAdjustedPoints for CircleHitBox {
+    fn get_adjusted_points_native(&mut self) -> &Vec<(f32, f32)> {
+        if self.cache_dirty {
+            self.adjusted_cache =
+                get_circle_points(self.position, self.adjusted_radius(), self.segments);
+            self.cache_dirty = false;
+        }
+
+        &self.adjusted_cache
+    }
+
+    fn get_bounds_native(&mut self) -> Aabb {
+        let radius = self.adjusted_radius();
+        Aabb {
+            left: self.position.0 - radius,
+            right: self.position.0 + radius,
+            bottom: self.position.1 - radius,
+            top: self.position.1 + radius,
+        }
+    }
+
+    fn get_bounding_circle_native(&mut self) -> ((f32, f32), f32) {
+        (self.position, self.adjusted_radius())
+    }
+
+    fn get_shape_native(&mut self) -> Shape<'_> {
+        Shape::Circle {
+            center: self.position,
+            radius: self.adjusted_radius(),
+        }
+    }
 }
 
+/// A segment with rounded ends, the usual smooth-sliding shape for characters.
+///
+/// The segment is rotated, scaled and moved the same way as a `RotatableHitBox`,
+/// while the radius uses the larger scale axis so the ends stay round.
 #[derive(Clone)]
-#[pyclass(module = "arcade.hitbox.base")]
-pub struct HitBox {
-    #[pyo3(get, set)]
-    pub points: Vec<(f32, f32)>,
+#[pyclass(subclass)]
+pub struct CapsuleHitBox {
+    #[pyo3(get)]
+    pub start: (f32, f32),
+    #[pyo3(get)]
+    pub end: (f32, f32),
+    #[pyo3(get)]
+    pub radius: f32,
     #[pyo3(get)]
     pub position: (f32, f32),
     #[pyo3(get)]
     pub scale: (f32, f32),
+    #[pyo3(get)]
     pub angle: f32,
+    /// Number of points used when the capsule is read as a polygon
+    #[pyo3(get)]
+    pub segments: usize,
 
+    pub adjusted_segment_cache: ((f32, f32), (f32, f32)),
     pub adjusted_cache: Vec<(f32, f32)>,
     pub cache_dirty: bool,
 }
 
+impl CapsuleHitBox {
+    pub fn get_adjusted_segment_native(&mut self) -> ((f32, f32), (f32, f32)) {
+        self.update_cache();
+        self.adjusted_segment_cache
+    }
+
+    fn update_cache(&mut self) {
+        if self.cache_dirty {
+            let segment = get_rotated_points(
+                &[self.start, self.end],
+                self.position,
+                self.scale,
+                self.angle,
+            );
+            let (start, end) = (segment[0], segment[1]);
+            self.adjusted_segment_cache = (start, end);
+            self.adjusted_cache =
+                get_capsule_points(start, end, self.adjusted_radius(), self.segments);
+            self.cache_dirty = false;
+        }
+    }
+}
+
+/// Points approximating a capsule: half a circle around `end` then half around `start`.
+pub fn get_capsule_points(
+    start: (f32, f32),
+    end: (f32, f32),
+    radius: f32,
+    segments: usize,
+) -> Vec<(f32, f32)> {
+    let direction = (end.1 - start.1).atan2(end.0 - start.0);
+    let half = (segments / 2).max(2);
+    let step = std::f32::consts::PI / (half - 1) as f32;
+    let mut points = Vec::with_capacity(half * 2);
+    for (center, offset) in [
+        (end, -std::f32::consts::FRAC_PI_2),
+        (start, std::f32::consts::FRAC_PI_2),
+    ] {
+        for i in 0..half {
+            let angle = direction + offset + step * i as f32;
+            points.push((
+                center.0 + radius * angle.cos(),
+                center.1 + radius * angle.sin(),
+            ));
+        }
+    }
+    points
+}
+
 #[pymethods]
-impl HitBox {
+impl CapsuleHitBox {
     #[new]
     fn new(
-        points: Vec<(f32, f32)>,
+        start: (f32, f32),
+        end: (f32, f32),
+        radius: f32,
         position: Option<(f32, f32)>,
         scale: Option<(f32, f32)>,
-    ) -> HitBox {
+        angle: Option<f32>,
+        segments: Option<usize>,
+    ) -> CapsuleHitBox {
         let final_position = position.unwrap_or((0.0, 0.0));
         let final_scale = scale.unwrap_or((1.0, 1.0));
-        HitBox {
-            points,
+        let final_angle = angle.unwrap_or(0.0);
+        let final_segments = segments.unwrap_or(32).max(4);
+        CapsuleHitBox {
+            start,
+            end,
+            radius,
             position: final_position,
             scale: final_scale,
-            angle: 0.0,
+            angle: final_angle,
+            segments: final_segments,
+            adjusted_segment_cache: ((0.0, 0.0), (0.0, 0.0)),
             adjusted_cache: vec![],
             cache_dirty: true,
         }
     }
 
-    fn create_rotatable(
-        self_: PyRef<'_, Self>,
-        py: Python<'_>,
-        angle: Option<f32>,
-    ) -> PyResult<Py<RotatableHitBox>> {
-        let adjustable: Py<RotatableHitBox> = Py::new(
-            py,
-            RotatableHitBox::new(
-                self_.points.to_vec(),
-                Some(self_.position),
-                Some(self_.scale),
-                angle,
-            ),
-        )
-        .unwrap();
-        Ok(adjustable)
+    fn create_rotatable(&self, angle: Option<f32>) -> CapsuleHitBox {
+        let mut rotatable = self.clone();
+        rotatable.angle = angle.unwrap_or(0.0);
+        rotatable.cache_dirty = true;
+        rotatable
+    }
+
+    #[getter]
+    pub fn points(&self) -> Vec<(f32, f32)> {
+        get_capsule_points(self.start, self.end, self.radius, self.segments)
+    }
+
+    #[getter]
+    pub fn adjusted_radius(&self) -> f32 {
+        self.radius * self.scale.0.abs().max(self.scale.1.abs())
     }
 
     pub fn get_adjusted_points(&mut self) -> Vec<(f32, f32)> {
-        if self.cache_dirty {
-            self.adjusted_cache = Vec::with_capacity(self.points.len());
-            for point in self.points.iter() {
-                let x = (point.0 * self.scale.0) + self.position.0;
-                let y = (point.1 * self.scale.1) + self.position.1;
-                self.adjusted_cache.push((x, y));
-            }
-            self.cache_dirty = false;
-        }
+        self.get_adjusted_points_native().to_vec()
+    }
+
+    #[getter]
+    pub fn adjusted_segment(&mut self) -> ((f32, f32), (f32, f32)) {
+        self.get_adjusted_segment_native()
+    }
 
-        self.adjusted_cache.to_vec()
+    #[setter]
+    pub fn set_radius(&mut self, value: f32) -> PyResult<()> {
+        self.radius = value;
+        self.cache_dirty = true;
+        Ok(())
     }
 
     #[setter]
@@ -85,56 +1031,86 @@ impl HitBox {
         Ok(())
     }
 
+    #[setter]
+    pub fn set_angle(&mut self, value: f32) -> PyResult<()> {
+        self.angle = value;
+        self.cache_dirty = true;
+        Ok(())
+    }
+
     #[getter]
     pub fn left(&mut self) -> PyResult<f32> {
-        let mut converted: Vec<(f32, f32)> = self.get_adjusted_points_native().to_vec();
-        converted.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
-        Ok(converted[0].0)
+        Ok(self.get_bounds_native().left)
     }
 
     #[getter]
     pub fn right(&mut self) -> PyResult<f32> {
-        let mut converted: Vec<(f32, f32)> = self.get_adjusted_points_native().to_vec();
-        converted.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
-        Ok(converted[0].0)
+        Ok(self.get_bounds_native().right)
     }
 
     #[getter]
     pub fn bottom(&mut self) -> PyResult<f32> {
-        let mut converted: Vec<(f32, f32)> = self.get_adjusted_points_native().to_vec();
-        converted.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
-        Ok(converted[0].1)
+        Ok(self.get_bounds_native().bottom)
     }
 
     #[getter]
     pub fn top(&mut self) -> PyResult<f32> {
-        let mut converted: Vec<(f32, f32)> = self.get_adjusted_points_native().to_vec();
-        converted.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
-        Ok(converted[0].1)
+        Ok(self.get_bounds_native().top)
+    }
+
+    /// The adjusted points' extent as `(left, right, bottom, top)`.
+    #[getter]
+    pub fn bounds(&mut self) -> (f32, f32, f32, f32) {
+        let bounds = self.get_bounds_native();
+        (bounds.left, bounds.right, bounds.bottom, bounds.top)
     }
 }
 
-impl NativeAdjustedPoints for HitBox {
+impl NativeAdjustedPoints for CapsuleHitBox {
     fn get_adjusted_points_native(&mut self) -> &Vec<(f32, f32)> {
-        if self.cache_dirty {
-            self.adjusted_cache = Vec::with_capacity(self.points.len());
-            for point in self.points.iter() {
-                let x = (point.0 * self.scale.0) + self.position.0;
-                let y = (point.1 * self.scale.1) + self.position.1;
-                self.adjusted_cache.push((x, y));
-            }
-            self.cache_dirty = false;
+        self.update_cache();
+        &self.adjusted_cache
+    }
+
+    fn get_bounds_native(&mut self) -> Aabb {
+        let (start, end) = self.get_adjusted_segment_native();
+        let radius = self.adjusted_radius();
+        let segment = Aabb::from_points(&[start, end]);
+        Aabb {
+            left: segment.left - radius,
+            right: segment.right + radius,
+            bottom: segment.bottom - radius,
+            top: segment.top + radius,
         }
+    }
 
-        &self.adjusted_cache
+    fn get_bounding_circle_native(&mut self) -> ((f32, f32), f32) {
+        let (start, end) = self.get_adjusted_segment_native();
+        let radius = self.adjusted_radius();
+        (
+            self.position,
+            get_bounding_radius(&[start, end], self.position) + radius,
+        )
+    }
+    fn get_shape_native(&mut self) -> Shape<'_> {
+        let (start, end) = self.get_adjusted_segment_native();
+        Shape::Capsule {
+            start,
+            end,
+            radius: self.adjusted_radius(),
+        }
     }
 }
 
+/// A hit box made of several convex polygons sharing one position, scale and angle.
+///
+/// It collides when any of its parts does, which also allows concave outlines
+/// to be used with the separating axis test.
 #[derive(Clone)]
-#[pyclass(module = "arcade.hitbox.base")]
-pub struct RotatableHitBox {
-    #[pyo3(get, set)]
-    pub points: Vec<(f32, f32)>,
+#[pyclass(subclass)]
+pub struct CompoundHitBox {
+    #[pyo3(get)]
+    pub parts: Vec<Vec<(f32, f32)>>,
     #[pyo3(get)]
     pub position: (f32, f32),
     #[pyo3(get)]
@@ -142,66 +1118,101 @@ pub struct RotatableHitBox {
     #[pyo3(get)]
     pub angle: f32,
 
+    pub adjusted_parts_cache: Vec<Vec<(f32, f32)>>,
+    /// Convex hull of every part, used for the bounds and by generic callers
     pub adjusted_cache: Vec<(f32, f32)>,
+    pub bounds_cache: Aabb,
+    pub radius_cache: f32,
     pub cache_dirty: bool,
 }
 
+impl CompoundHitBox {
+    pub fn get_adjusted_parts_native(&mut self) -> &[Vec<(f32, f32)>] {
+        self.update_cache();
+        &self.adjusted_parts_cache
+    }
+
+    fn update_cache(&mut self) {
+        if self.cache_dirty {
+            self.adjusted_parts_cache = self
+                .parts
+                .iter()
+                .map(|part| get_rotated_points(part, self.position, self.scale, self.angle))
+                .collect();
+            self.adjusted_cache = get_convex_hull_native(&self.adjusted_parts_cache.concat());
+            self.bounds_cache = Aabb::from_points(&self.adjusted_cache);
+            self.radius_cache = get_bounding_radius(&self.adjusted_cache, self.position);
+            self.cache_dirty = false;
+        }
+    }
+}
+
 #[pymethods]
-impl RotatableHitBox {
+impl CompoundHitBox {
     #[new]
     fn new(
-        points: Vec<(f32, f32)>,
+        parts: Vec<Vec<(f32, f32)>>,
         position: Option<(f32, f32)>,
         scale: Option<(f32, f32)>,
         angle: Option<f32>,
-    ) -> RotatableHitBox {
+    ) -> CompoundHitBox {
         let final_position = position.unwrap_or((0.0, 0.0));
         let final_scale = scale.unwrap_or((1.0, 1.0));
         let final_angle = angle.unwrap_or(0.0);
-        RotatableHitBox {
-            points,
+        CompoundHitBox {
+            parts,
             position: final_position,
             scale: final_scale,
             angle: final_angle,
+            adjusted_parts_cache: vec![],
             adjusted_cache: vec![],
+            bounds_cache: Aabb::EMPTY,
+            radius_cache: 0.0,
             cache_dirty: true,
         }
     }
 
-    fn create_rotatable(
-        self_: PyRef<'_, Self>,
-        py: Python<'_>,
-        angle: Option<f32>,
-    ) -> PyResult<Py<RotatableHitBox>> {
-        let adjustable: Py<RotatableHitBox> = Py::new(
-            py,
-            RotatableHitBox::new(
-                self_.points.to_vec(),
-                Some(self_.position),
-                Some(self_.scale),
-                angle,
-            ),
-        )
-        .unwrap();
-        Ok(adjustable)
+    fn create_rotatable(&self, angle: Option<f32>) -> CompoundHitBox {
+        let mut rotatable = self.clone();
+        rotatable.angle = angle.unwrap_or(0.0);
+        rotatable.cache_dirty = true;
+        rotatable
+    }
+
+    /// The convex hull of all parts, a single polygon for code expecting one. Use
+    /// `parts` and `get_adjusted_parts()` for the parts themselves.
+    #[getter]
+    pub fn points(&self) -> Vec<(f32, f32)> {
+        get_convex_hull_native(&self.parts.concat())
     }
 
+    /// The convex hull of all adjusted parts.
     pub fn get_adjusted_points(&mut self) -> Vec<(f32, f32)> {
-        if self.cache_dirty {
-            self.adjusted_cache = Vec::with_capacity(self.points.len());
-
-            let rad = self.angle.to_radians();
-            let rad_cos = rad.cos();
-            let rad_sin = rad.sin();
-            for point in self.points.iter() {
-                let x = ((point.0 * rad_cos + point.1 * rad_sin) * self.scale.0) + self.position.0;
-                let y = ((-point.0 * rad_sin + point.1 * rad_cos) * self.scale.1) + self.position.1;
-                self.adjusted_cache.push((x, y));
-            }
-            self.cache_dirty = false;
-        }
+        self.get_adjusted_points_native().to_vec()
+    }
+
+    pub fn get_adjusted_parts(&mut self) -> Vec<Vec<(f32, f32)>> {
+        self.get_adjusted_parts_native().to_vec()
+    }
+
+    /// Indices of the parts colliding with another hit box.
+    pub fn get_colliding_parts(&mut self, py: Python<'_>, other: &PyAny) -> PyResult<Vec<usize>> {
+        let mut other_hitbox = ExtractedHitBox::from_hit_box(py, other)?;
+        let other_shape = other_hitbox.shape();
+        Ok(self
+            .get_adjusted_parts_native()
+            .iter()
+            .enumerate()
+            .filter(|(_, part)| Shape::Polygon(part).intersects(&other_shape))
+            .map(|(index, _)| index)
+            .collect())
+    }
 
-        self.adjusted_cache.to_vec()
+    #[setter]
+    pub fn set_parts(&mut self, value: Vec<Vec<(f32, f32)>>) -> PyResult<()> {
+        self.parts = value;
+        self.cache_dirty = true;
+        Ok(())
     }
 
     #[setter]
@@ -227,51 +1238,191 @@ impl RotatableHitBox {
 
     #[getter]
     pub fn left(&mut self) -> PyResult<f32> {
-        let mut converted: Vec<(f32, f32)> = self.get_adjusted_points_native().to_vec();
-        converted.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
-        Ok(converted[0].0)
+        Ok(self.get_bounds_native().left)
     }
 
     #[getter]
     pub fn right(&mut self) -> PyResult<f32> {
-        let mut converted: Vec<(f32, f32)> = self.get_adjusted_points_native().to_vec();
-        converted.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
-        Ok(converted[0].0)
+        Ok(self.get_bounds_native().right)
     }
 
     #[getter]
     pub fn bottom(&mut self) -> PyResult<f32> {
-        let mut converted: Vec<(f32, f32)> = self.get_adjusted_points_native().to_vec();
-        converted.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
-        Ok(converted[0].1)
+        Ok(self.get_bounds_native().bottom)
     }
 
     #[getter]
     pub fn top(&mut self) -> PyResult<f32> {
-        let mut converted: Vec<(f32, f32)> = self.get_adjusted_points_native().to_vec();
-        converted.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
-        Ok(converted[0].1)
+        Ok(self.get_bounds_native().top)
+    }
+
+    /// The adjusted points' extent as `(left, right, bottom, top)`.
+    #[getter]
+    pub fn bounds(&mut self) -> (f32, f32, f32, f32) {
+        let bounds = self.get_bounds_native();
+        (bounds.left, bounds.right, bounds.bottom, bounds.top)
     }
 }
 
-impl NativeAdjustedPoints for RotatableHitBox {
+impl NativeAdjustedPoints for CompoundHitBox {
     fn get_adjusted_points_native(&mut self) -> &Vec<(f32, f32)> {
-        if self.cache_dirty {
-            self.adjusted_cache = Vec::with_capacity(self.points.len());
-
-            let rad = self.angle.to_radians();
-            let rad_cos = rad.cos();
-            let rad_sin = rad.sin();
-            for point in self.points.iter() {
-                let x = ((point.0 * rad_cos + point.1 * rad_sin) * self.scale.0) + self.position.0;
-                let y = ((-point.0 * rad_sin + point.1 * rad_cos) * self.scale.1) + self.position.1;
-                self.adjusted_cache.push((x, y));
+        self.update_cache();
+        &self.adjusted_cache
+    }
+
+    fn get_bounds_native(&mut self) -> Aabb {
+        self.update_cache();
+        self.bounds_cache
+    }
+
+    fn get_bounding_circle_native(&mut self) -> ((f32, f32), f32) {
+        self.update_cache();
+        (self.position, self.radius_cache)
+    }
+
+    fn get_shape_native(&mut self) -> Shape<'_> {
+        Shape::Compound(self.get_adjusted_parts_native())
+    }
+}
+
+/// Hit boxes whose transform `update_hit_box_transforms` can set.
+pub trait NativeTransform {
+    /// Check that `set_transform_native` would accept the angle, without changing
+    /// anything.
+    fn check_transform_native(&self, angle: Option<f32>) -> PyResult<()>;
+
+    /// Set the given parts of the transform and mark the cached points dirty. Raises
+    /// `TypeError` when given an angle but the hit box cannot rotate.
+    fn set_transform_native(
+        &mut self,
+        position: Option<(f32, f32)>,
+        scale: Option<(f32, f32)>,
+        angle: Option<f32>,
+    ) -> PyResult<()>;
+}
+
+macro_rules! impl_native_transform {
+    ($hitbox:ty, rotatable: $rotatable:literal) => {
+        impl NativeTransform for $hitbox {
+            fn check_transform_native(&self, angle: Option<f32>) -> PyResult<()> {
+                if angle.is_some() && !$rotatable {
+                    return Err(PyTypeError::new_err(format!(
+                        "{} can not be rotated",
+                        stringify!($hitbox)
+                    )));
+                }
+                Ok(())
+            }
+
+            fn set_transform_native(
+                &mut self,
+                position: Option<(f32, f32)>,
+                scale: Option<(f32, f32)>,
+                angle: Option<f32>,
+            ) -> PyResult<()> {
+                self.check_transform_native(angle)?;
+                if let Some(angle) = angle {
+                    self.angle = angle;
+                }
+                if let Some(position) = position {
+                    self.position = position;
+                }
+                if let Some(scale) = scale {
+                    self.scale = scale;
+                }
+                self.cache_dirty = true;
+                Ok(())
             }
-            self.cache_dirty = false;
         }
+    };
+}
 
-        &self.adjusted_cache
+impl_native_transform!(HitBox, rotatable: false);
+impl_native_transform!(RotatableHitBox, rotatable: true);
+impl_native_transform!(CircleHitBox, rotatable: true);
+impl_native_transform!(CapsuleHitBox, rotatable: true);
+impl_native_transform!(CompoundHitBox, rotatable: true);
+
+/// Call `f` with a native hit box borrowed mutably, including Python subclasses.
+fn with_native_transform<R>(
+    hit_box: &PyAny,
+    f: impl FnOnce(&mut dyn NativeTransform) -> PyResult<R>,
+) -> PyResult<R> {
+    if let Ok(hit_box) = hit_box.downcast::<PyCell<HitBox>>() {
+        f(&mut *hit_box.try_borrow_mut()?)
+    } else if let Ok(hit_box) = hit_box.downcast::<PyCell<RotatableHitBox>>() {
+        f(&mut *hit_box.try_borrow_mut()?)
+    } else if let Ok(hit_box) = hit_box.downcast::<PyCell<CircleHitBox>>() {
+        f(&mut *hit_box.try_borrow_mut()?)
+    } else if let Ok(hit_box) = hit_box.downcast::<PyCell<CapsuleHitBox>>() {
+        f(&mut *hit_box.try_borrow_mut()?)
+    } else if let Ok(hit_box) = hit_box.downcast::<PyCell<CompoundHitBox>>() {
+        f(&mut *hit_box.try_borrow_mut()?)
+    } else {
+        Err(PyTypeError::new_err(format!(
+            "Unknown hit box type '{}', expected a native hit box",
+            hit_box.get_type().name()?
+        )))
+    }
+}
+
+/// Set the positions, scales and angles of many native hit boxes in one call.
+///
+/// Each given array needs one entry per hit box: positions and scales as sequences of
+/// pairs or N×2 buffers, angles as sequences of numbers or buffers. Values left out
+/// keep their current value. The fields are set directly, so property setters
+/// overridden by Python subclasses are not called. Nothing is changed when any hit box
+/// is not native or is given an angle it can not take.
+#[pyfunction]
+pub fn update_hit_box_transforms(
+    hit_boxes: Vec<&PyAny>,
+    positions: Option<&PyAny>,
+    scales: Option<&PyAny>,
+    angles: Option<&PyAny>,
+) -> PyResult<()> {
+    let count = hit_boxes.len();
+    let check_length = |name: &str, length: usize| {
+        if length == count {
+            Ok(())
+        } else {
+            Err(PyValueError::new_err(format!(
+                "expected {} {}, one per hit box, but got {}",
+                count, name, length
+            )))
+        }
+    };
+
+    let positions = positions.map(extract_shared_points).transpose()?;
+    let scales = scales.map(extract_shared_points).transpose()?;
+    let angles = angles.map(extract_values).transpose()?;
+    if let Some(positions) = &positions {
+        check_length("positions", positions.len())?;
     }
+    if let Some(scales) = &scales {
+        check_length("scales", scales.len())?;
+    }
+    if let Some(angles) = &angles {
+        check_length("angles", angles.len())?;
+    }
+
+    let angle_at = |index: usize| angles.as_ref().map(|angles| angles[index]);
+
+    // Check every hit box before changing any, so an error leaves them all as they were
+    for (index, hit_box) in hit_boxes.iter().enumerate() {
+        with_native_transform(hit_box, |hit_box| {
+            hit_box.check_transform_native(angle_at(index))
+        })?;
+    }
+    for (index, hit_box) in hit_boxes.into_iter().enumerate() {
+        with_native_transform(hit_box, |hit_box| {
+            hit_box.set_transform_native(
+                positions.as_ref().map(|positions| positions[index]),
+                scales.as_ref().map(|scales| scales[index]),
+                angle_at(index),
+            )
+        })?;
+    }
+    Ok(())
 }
 
 // #[derive(Clone)]
@@ -360,3 +1511,322 @@ impl NativeAdjustedPoints for RotatableHitBox {
 //         new_points
 //     }
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::types::PyList;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_set_transform_native() {
+        let mut hitbox = RotatableHitBox::new_native(vec![(1.0, 0.0)], None, None, None);
+        assert_eq!(hitbox.get_adjusted_points_native()[0], (1.0, 0.0));
+
+        hitbox
+            .set_transform_native(Some((5.0, 5.0)), Some((2.0, 2.0)), None)
+            .unwrap();
+        assert_eq!(hitbox.get_adjusted_points_native()[0], (7.0, 5.0));
+        hitbox.set_transform_native(None, None, Some(90.0)).unwrap();
+        let point = hitbox.get_adjusted_points_native()[0];
+        assert!((point.0 - 5.0).abs() < 1.0e-5 && (point.1 - 3.0).abs() < 1.0e-5);
+        assert_eq!(hitbox.scale, (2.0, 2.0));
+
+        let mut hitbox = HitBox::new_native(vec![(1.0, 0.0)], None, None);
+        assert!(hitbox.set_transform_native(None, None, Some(90.0)).is_err());
+        hitbox
+            .set_transform_native(Some((1.0, 1.0)), None, None)
+            .unwrap();
+        assert_eq!(hitbox.get_adjusted_points_native()[0], (2.0, 1.0));
+    }
+
+    #[test]
+    fn test_update_hit_box_transforms_unchanged_on_error() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let rotatable = PyCell::new(
+                py,
+                RotatableHitBox::new_native(vec![(1.0, 0.0)], None, None, None),
+            )
+            .unwrap();
+            let plain = PyCell::new(py, HitBox::new_native(vec![(1.0, 0.0)], None, None)).unwrap();
+            let positions = PyList::new(py, [(5.0, 5.0), (6.0, 6.0)]);
+            let angles = PyList::new(py, [10.0, 20.0]);
+            let result = update_hit_box_transforms(
+                vec![rotatable.as_ref(), plain.as_ref()],
+                Some(positions),
+                None,
+                Some(angles),
+            );
+            assert!(result.unwrap_err().is_instance_of::<PyTypeError>(py));
+            assert_eq!(rotatable.borrow().position, (0.0, 0.0));
+            assert_eq!(rotatable.borrow().angle, 0.0);
+            assert_eq!(plain.borrow().position, (0.0, 0.0));
+        });
+    }
+
+    #[test]
+    fn test_hitbox_bounds_cache() {
+        let mut hitbox = HitBox::new_native(
+            vec![(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)],
+            Some((10.0, 20.0)),
+            Some((2.0, 3.0)),
+        );
+        let bounds = hitbox.get_bounds_native();
+        assert_eq!((bounds.left, bounds.right), (8.0, 12.0));
+        assert_eq!((bounds.bottom, bounds.top), (17.0, 23.0));
+        assert_eq!(
+            hitbox.get_bounding_circle_native(),
+            ((10.0, 20.0), 13.0_f32.sqrt())
+        );
+
+        hitbox.set_position((0.0, 0.0)).unwrap();
+        assert_eq!(hitbox.get_bounds_native().left, -2.0);
+    }
+
+    #[test]
+    fn test_hitboxes_share_points() {
+        let point_set =
+            PointSet::from(SharedPoints::from(vec![(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)]));
+        let mut first = HitBox::new_native(point_set.points.clone(), Some((5.0, 0.0)), None);
+        let mut second =
+            RotatableHitBox::new_native(point_set.points.clone(), None, None, Some(90.0));
+        assert!(Arc::ptr_eq(&first.points, &point_set.points));
+        assert!(Arc::ptr_eq(&second.points, &first.points));
+        assert_eq!(first.get_adjusted_points_native()[1], (6.0, 0.0));
+        assert!((second.get_adjusted_points_native()[1].1 - -1.0).abs() < 1.0e-6);
+    }
+
+    #[test]
+    fn test_hitbox_edge_getters() {
+        let mut hitbox = HitBox::new_native(
+            vec![(-1.0, -2.0), (3.0, -1.0), (2.0, 4.0)],
+            Some((10.0, 0.0)),
+            None,
+        );
+        assert_eq!(hitbox.left().unwrap(), 9.0);
+        assert_eq!(hitbox.right().unwrap(), 13.0);
+        assert_eq!(hitbox.bottom().unwrap(), -2.0);
+        assert_eq!(hitbox.top().unwrap(), 4.0);
+        assert_eq!(hitbox.bounds(), (9.0, 13.0, -2.0, 4.0));
+
+        hitbox.set_points_native(vec![(0.0, 0.0), (1.0, 1.0)]);
+        assert_eq!(hitbox.bounds(), (10.0, 11.0, 0.0, 1.0));
+        hitbox.set_scale((-1.0, 1.0)).unwrap();
+        assert_eq!(hitbox.bounds(), (9.0, 10.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn test_circle_hitbox() {
+        let mut hitbox = CircleHitBox::new(5.0, Some((10.0, 10.0)), Some((1.0, 2.0)), Some(4));
+        assert_eq!(hitbox.adjusted_radius(), 10.0);
+        assert_eq!(hitbox.get_bounds_native().left, 0.0);
+        assert_eq!(hitbox.get_bounding_circle_native(), ((10.0, 10.0), 10.0));
+
+        let points = hitbox.get_adjusted_points_native().clone();
+        assert_eq!(points.len(), 4);
+        assert_eq!(points[0], (20.0, 10.0));
+
+        assert_eq!(
+            hitbox.get_shape_native(),
+            Shape::Circle {
+                center: (10.0, 10.0),
+                radius: 10.0
+            }
+        );
+    }
+
+    #[test]
+    fn test_capsule_hitbox() {
+        let mut hitbox = CapsuleHitBox::new(
+            (0.0, -10.0),
+            (0.0, 10.0),
+            5.0,
+            Some((100.0, 0.0)),
+            None,
+            Some(90.0),
+            None,
+        );
+        let (start, end) = hitbox.get_adjusted_segment_native();
+        assert!((start.0 - 90.0).abs() < 1.0e-4 && start.1.abs() < 1.0e-4);
+        assert!((end.0 - 110.0).abs() < 1.0e-4 && end.1.abs() < 1.0e-4);
+
+        let bounds = hitbox.get_bounds_native();
+        assert!((bounds.left - 85.0).abs() < 1.0e-4);
+        assert!((bounds.top - 5.0).abs() < 1.0e-4);
+        assert_eq!(hitbox.get_bounding_circle_native().1, 15.0);
+        assert_eq!(hitbox.get_adjusted_points_native().len(), 32);
+
+        // Every approximating point lies on the capsule outline
+        let (start, end) = hitbox.get_adjusted_segment_native();
+        for point in hitbox.get_adjusted_points_native().clone() {
+            let distance = crate::geometry::get_point_segment_distance_squared(point, start, end);
+            assert!((distance.sqrt() - 5.0).abs() < 1.0e-3);
+        }
+    }
+
+    #[test]
+    fn test_compound_hitbox() {
+        // An L shape made of two rectangles
+        let mut hitbox = CompoundHitBox::new(
+            vec![
+                vec![(0.0, 0.0), (10.0, 0.0), (10.0, 2.0), (0.0, 2.0)],
+                vec![(0.0, 2.0), (2.0, 2.0), (2.0, 10.0), (0.0, 10.0)],
+            ],
+            Some((100.0, 100.0)),
+            None,
+            None,
+        );
+        let bounds = hitbox.get_bounds_native();
+        assert_eq!((bounds.left, bounds.right), (100.0, 110.0));
+        assert_eq!((bounds.bottom, bounds.top), (100.0, 110.0));
+        // Code expecting a single polygon gets the outline around both parts
+        assert_eq!(
+            hitbox.get_adjusted_points_native(),
+            &vec![
+                (100.0, 100.0),
+                (110.0, 100.0),
+                (110.0, 102.0),
+                (102.0, 110.0),
+                (100.0, 110.0)
+            ]
+        );
+        assert_eq!(hitbox.points().len(), 5);
+        assert_eq!(hitbox.get_adjusted_parts_native()[1][2], (102.0, 110.0));
+
+        // Inside the bounding box but in the empty corner of the L
+        let corner = [
+            (105.0, 105.0),
+            (108.0, 105.0),
+            (108.0, 108.0),
+            (105.0, 108.0),
+        ];
+        let arm = [
+            (101.0, 105.0),
+            (108.0, 105.0),
+            (108.0, 108.0),
+            (101.0, 108.0),
+        ];
+        assert!(!hitbox
+            .get_shape_native()
+            .intersects(&Shape::Polygon(&corner)));
+        assert!(hitbox.get_shape_native().intersects(&Shape::Polygon(&arm)));
+    }
+
+    #[test]
+    fn test_copy_subclass() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let globals = PyDict::new(py);
+            globals
+                .set_item("RotatableHitBox", py.get_type::<RotatableHitBox>())
+                .unwrap();
+            globals.set_item("__name__", "hitbox_copy_test").unwrap();
+            // A constructor taking other arguments, which copying must not call again
+            py.run(
+                r#"
+import copy
+import pickle
+import sys
+import types
+
+# Pickle finds the native classes by their module, as arcade would provide it
+for name in ["arcade", "arcade.hitbox", "arcade.hitbox.base"]:
+    sys.modules[name] = types.ModuleType(name)
+sys.modules["arcade.hitbox.base"].RotatableHitBox = RotatableHitBox
+
+class Owner:
+    pass
+
+class Tagged(RotatableHitBox):
+    created = 0
+
+    def __new__(cls, tag):
+        return super().__new__(cls, [(0, 0), (2, 0), (0, 2)])
+
+    def __init__(self, tag):
+        Tagged.created += 1
+        self.tag = tag
+
+sys.modules[__name__] = types.ModuleType(__name__)
+sys.modules[__name__].Owner = Owner
+sys.modules[__name__].Tagged = Tagged
+
+original = Tagged("wall")
+original.position = (5, 6)
+original.angle = 90
+copies = [
+    copy.copy(original),
+    copy.deepcopy(original),
+    pickle.loads(pickle.dumps(original)),
+]
+
+# An attribute pointing back at the hit box must point at the copy after deepcopy
+owner = Owner()
+original.owner = owner
+owner.hit_box = original
+cycle = copy.deepcopy(original)
+cycle_kept = cycle.owner.hit_box is cycle
+del original.owner
+"#,
+                Some(globals),
+                None,
+            )
+            .unwrap();
+            let original = globals.get_item("original").unwrap().unwrap();
+            let copies: Vec<&PyAny> = globals
+                .get_item("copies")
+                .unwrap()
+                .unwrap()
+                .extract()
+                .unwrap();
+            for copy in copies {
+                assert!(copy.get_type().is(original.get_type()));
+                assert_eq!(
+                    copy.getattr("tag").unwrap().extract::<&str>().unwrap(),
+                    "wall"
+                );
+                let copy = copy.downcast::<PyCell<RotatableHitBox>>().unwrap();
+                assert_eq!(copy.borrow().position, (5.0, 6.0));
+                assert_eq!(
+                    copy.borrow_mut().get_adjusted_points(),
+                    original
+                        .downcast::<PyCell<RotatableHitBox>>()
+                        .unwrap()
+                        .borrow_mut()
+                        .get_adjusted_points()
+                );
+            }
+            let created: usize = globals
+                .get_item("Tagged")
+                .unwrap()
+                .unwrap()
+                .getattr("created")
+                .unwrap()
+                .extract()
+                .unwrap();
+            assert_eq!(created, 1);
+            let cycle_kept: bool = globals
+                .get_item("cycle_kept")
+                .unwrap()
+                .unwrap()
+                .extract()
+                .unwrap();
+            assert!(cycle_kept);
+        });
+    }
+
+    #[test]
+    fn test_rotatable_hitbox_bounds_cache() {
+        let mut hitbox = RotatableHitBox::new_native(
+            vec![(0.0, 0.0), (4.0, 0.0), (4.0, 1.0), (0.0, 1.0)],
+            None,
+            None,
+            Some(90.0),
+        );
+        let bounds = hitbox.get_bounds_native();
+        assert!((bounds.bottom - -4.0).abs() < 1.0e-5);
+        assert!((bounds.right - 1.0).abs() < 1.0e-5);
+        assert!((hitbox.get_bounding_circle_native().1 - 17.0_f32.sqrt()).abs() < 1.0e-5);
+    }
+}
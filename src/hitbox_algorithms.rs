@@ -0,0 +1,487 @@
+use std::collections::BTreeMap;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::decomposition::decompose_polygon_native;
+use crate::geometry::{
+    get_polygon_signed_area_native, normalize_polygon_winding_native, simplify_polygon_rdp_native,
+};
+
+const DEFAULT_DETAIL: f32 = 4.5;
+const DEFAULT_ALPHA_THRESHOLD: u8 = 127;
+
+/// Alpha channel of a tightly packed RGBA image, with rows from the top down.
+pub struct AlphaImage<'a> {
+    data: &'a [u8],
+    pub width: u32,
+    pub height: u32,
+}
+
+impl<'a> AlphaImage<'a> {
+    pub fn new(data: &'a [u8], width: u32, height: u32) -> PyResult<AlphaImage<'a>> {
+        let expected = width as usize * height as usize * 4;
+        if data.len() != expected {
+            return Err(PyValueError::new_err(format!(
+                "Expected {} bytes of RGBA data for a {}x{} image, got {}",
+                expected,
+                width,
+                height,
+                data.len()
+            )));
+        }
+        Ok(AlphaImage {
+            data,
+            width,
+            height,
+        })
+    }
+
+    /// Alpha at a pixel, treating anything outside the image as transparent.
+    pub fn alpha(&self, x: i64, y: i64) -> u8 {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return 0;
+        }
+        self.data[(y as usize * self.width as usize + x as usize) * 4 + 3]
+    }
+
+    /// Smallest rectangle holding every non-transparent pixel, as inclusive
+    /// `(left, top, right, bottom)` pixel coordinates.
+    pub fn get_bbox(&self) -> Option<(i64, i64, i64, i64)> {
+        let mut bbox: Option<(i64, i64, i64, i64)> = None;
+        for y in 0..self.height as i64 {
+            for x in 0..self.width as i64 {
+                if self.alpha(x, y) != 0 {
+                    bbox = Some(match bbox {
+                        None => (x, y, x, y),
+                        Some((l, t, r, b)) => (l.min(x), t.min(y), r.max(x), b.max(y)),
+                    });
+                }
+            }
+        }
+        bbox
+    }
+
+    /// Convert a pixel corner coordinate to a point centered on the image, y up.
+    pub fn to_point(&self, x: f32, y: f32) -> (f32, f32) {
+        let width = self.width as f32;
+        let height = self.height as f32;
+        (x - width / 2.0, (height - y) - height / 2.0)
+    }
+
+    pub fn create_bounding_box(&self) -> Vec<(f32, f32)> {
+        get_bounding_box_points(self.width, self.height)
+    }
+}
+
+fn get_bounding_box_points(width: u32, height: u32) -> Vec<(f32, f32)> {
+    let half_width = width as f32 / 2.0;
+    let half_height = height as f32 / 2.0;
+    vec![
+        (-half_width, -half_height),
+        (half_width, -half_height),
+        (half_width, half_height),
+        (-half_width, half_height),
+    ]
+}
+
+/// How far the transparent triangle in a corner of the bounding box reaches, walking
+/// diagonals away from the corner until one of them holds an opaque pixel.
+fn check_corner_offset(
+    image: &AlphaImage,
+    start_x: i64,
+    start_y: i64,
+    x_direction: i64,
+    y_direction: i64,
+) -> i64 {
+    let limit = image.width as i64 + image.height as i64;
+    let mut offset = 0;
+    while offset <= limit {
+        let mut x = start_x;
+        let mut y = start_y + (offset * y_direction);
+        for _ in 0..(offset + 1) {
+            if image.alpha(x, y) != 0 {
+                return offset;
+            }
+            y -= y_direction;
+            x += x_direction;
+        }
+        offset += 1;
+    }
+    offset
+}
+
+/// Port of arcade's `SimpleHitBoxAlgorithm`: the bounding box of the opaque pixels
+/// with each corner cut off diagonally where it is transparent.
+pub fn calculate_hit_box_points_simple_native(image: &AlphaImage) -> Vec<(f32, f32)> {
+    let Some((left_border, top_border, right_border, bottom_border)) = image.get_bbox() else {
+        return image.create_bounding_box();
+    };
+
+    let top_left_corner_offset = check_corner_offset(image, left_border, top_border, 1, 1);
+    let top_right_corner_offset = check_corner_offset(image, right_border, top_border, -1, 1);
+    let bottom_left_corner_offset = check_corner_offset(image, left_border, bottom_border, 1, -1);
+    let bottom_right_corner_offset =
+        check_corner_offset(image, right_border, bottom_border, -1, -1);
+
+    let p1 = (left_border + top_left_corner_offset, top_border);
+    let p2 = ((right_border + 1) - top_right_corner_offset, top_border);
+    let p3 = (right_border + 1, top_border + top_right_corner_offset);
+    let p4 = (
+        right_border + 1,
+        (bottom_border + 1) - bottom_right_corner_offset,
+    );
+    let p5 = (
+        (right_border + 1) - bottom_right_corner_offset,
+        bottom_border + 1,
+    );
+    let p6 = (left_border + bottom_left_corner_offset, bottom_border + 1);
+    let p7 = (left_border, (bottom_border + 1) - bottom_left_corner_offset);
+    let p8 = (left_border, top_border + top_left_corner_offset);
+
+    let mut corners: Vec<(i64, i64)> = Vec::with_capacity(8);
+    corners.push(p7);
+    if bottom_left_corner_offset != 0 {
+        corners.push(p6);
+    }
+    corners.push(p5);
+    if bottom_right_corner_offset != 0 {
+        corners.push(p4);
+    }
+    corners.push(p3);
+    if top_right_corner_offset != 0 {
+        corners.push(p2);
+    }
+    corners.push(p1);
+    if top_left_corner_offset != 0 {
+        corners.push(p8);
+    }
+
+    // Remove duplicates, keeping the first occurrence
+    let mut result: Vec<(f32, f32)> = Vec::with_capacity(corners.len());
+    for corner in corners {
+        let point = image.to_point(corner.0 as f32, corner.1 as f32);
+        if !result.contains(&point) {
+            result.push(point);
+        }
+    }
+    result
+}
+
+#[pyfunction]
+pub fn calculate_hit_box_points_simple(
+    image_data: &[u8],
+    width: u32,
+    height: u32,
+) -> PyResult<Vec<(f32, f32)>> {
+    let image = AlphaImage::new(image_data, width, height)?;
+    Ok(calculate_hit_box_points_simple_native(&image))
+}
+
+/// Port of arcade's `BoundingHitBoxAlgorithm`, the full rectangle of the image.
+#[pyfunction]
+pub fn calculate_hit_box_points_bounding(
+    image_data: &[u8],
+    width: u32,
+    height: u32,
+) -> PyResult<Vec<(f32, f32)>> {
+    let image = AlphaImage::new(image_data, width, height)?;
+    Ok(image.create_bounding_box())
+}
+
+/// A grid edge between two neighbouring alpha samples, keyed by its first sample and
+/// whether it runs horizontally.
+type SampleEdge = (i64, i64, bool);
+
+/// Trace the outlines of the regions whose alpha is above `threshold` with marching
+/// squares, one sample per pixel center.
+///
+/// Crossings are interpolated between samples, so soft edges give smooth outlines.
+/// Loops are returned closed (without repeating the first point) in hit box
+/// coordinates, outer outlines counter-clockwise and holes clockwise. Pixels touching
+/// only at a corner are joined when the average of the four samples is above the
+/// threshold.
+pub fn trace_alpha_contours_native(image: &AlphaImage, threshold: u8) -> Vec<Vec<(f32, f32)>> {
+    let is_inside = |x: i64, y: i64| image.alpha(x, y) > threshold;
+    let crossing = |(x, y, horizontal): SampleEdge| {
+        let (x2, y2) = if horizontal { (x + 1, y) } else { (x, y + 1) };
+        let a = image.alpha(x, y) as f32;
+        let b = image.alpha(x2, y2) as f32;
+        // Half way between the largest outside and smallest inside alpha, so fully
+        // opaque pixels next to transparent ones give crossings on the pixel border
+        let level = threshold as f32 + 0.5;
+        let t = ((level - a) / (b - a)).clamp(0.0, 1.0);
+        image.to_point(
+            x as f32 + 0.5 + (x2 - x) as f32 * t,
+            y as f32 + 0.5 + (y2 - y) as f32 * t,
+        )
+    };
+
+    // Each crossed edge leads to exactly one other crossed edge, going round the
+    // inside of every region the same way.
+    let mut next_edge: BTreeMap<SampleEdge, SampleEdge> = BTreeMap::new();
+    for y in -1..image.height as i64 {
+        for x in -1..image.width as i64 {
+            let corners = [(x, y), (x + 1, y), (x + 1, y + 1), (x, y + 1)];
+            let inside = corners.map(|(cx, cy)| is_inside(cx, cy));
+            // Cell sides in corner order: top, right, bottom, left
+            let sides: [SampleEdge; 4] = [
+                (x, y, true),
+                (x + 1, y, false),
+                (x, y + 1, true),
+                (x, y, false),
+            ];
+            let entering = |k: usize| !inside[k] && inside[(k + 1) % 4];
+            let leaving = |k: usize| inside[k] && !inside[(k + 1) % 4];
+
+            if inside == [true, false, true, false] || inside == [false, true, false, true] {
+                // Saddle, resolved by the average of the corners
+                let center: f32 = corners
+                    .iter()
+                    .map(|&(cx, cy)| image.alpha(cx, cy) as f32)
+                    .sum::<f32>()
+                    / 4.0;
+                let joined = center > threshold as f32;
+                for k in (0..4).filter(|&k| entering(k)) {
+                    let end = if joined { (k + 3) % 4 } else { (k + 1) % 4 };
+                    next_edge.insert(sides[k], sides[end]);
+                }
+                continue;
+            }
+
+            if let Some(start) = (0..4).find(|&k| entering(k)) {
+                let end = (1..4)
+                    .map(|offset| (start + offset) % 4)
+                    .find(|&k| leaving(k))
+                    .unwrap();
+                next_edge.insert(sides[start], sides[end]);
+            }
+        }
+    }
+
+    let mut contours: Vec<Vec<(f32, f32)>> = Vec::new();
+    while let Some((&first, _)) = next_edge.first_key_value() {
+        let mut contour: Vec<(f32, f32)> = Vec::new();
+        let mut edge = first;
+        while let Some(next) = next_edge.remove(&edge) {
+            contour.push(crossing(edge));
+            edge = next;
+        }
+        contours.push(contour);
+    }
+    contours
+}
+
+/// Replacement for arcade's pymunk based detailed hit box algorithm.
+///
+/// Traces the image with marching squares, keeps the outline enclosing the largest
+/// area and simplifies it so no pixel of the outline is further than `detail` away.
+/// The points are counter-clockwise. Images without any pixel above the threshold
+/// get their bounding box.
+pub fn calculate_hit_box_points_detailed_native(
+    image: &AlphaImage,
+    detail: f32,
+    threshold: u8,
+) -> Vec<(f32, f32)> {
+    let largest = trace_alpha_contours_native(image, threshold)
+        .into_iter()
+        .max_by(|a, b| {
+            get_polygon_signed_area_native(a)
+                .abs()
+                .total_cmp(&get_polygon_signed_area_native(b).abs())
+        });
+    let Some(contour) = largest else {
+        return image.create_bounding_box();
+    };
+    let mut points = simplify_polygon_rdp_native(&contour, detail);
+    normalize_polygon_winding_native(&mut points, false);
+    points
+}
+
+#[pyfunction]
+pub fn calculate_hit_box_points_detailed(
+    image_data: &[u8],
+    width: u32,
+    height: u32,
+    detail: Option<f32>,
+    threshold: Option<u8>,
+) -> PyResult<Vec<(f32, f32)>> {
+    let image = AlphaImage::new(image_data, width, height)?;
+    Ok(calculate_hit_box_points_detailed_native(
+        &image,
+        detail.unwrap_or(DEFAULT_DETAIL),
+        threshold.unwrap_or(DEFAULT_ALPHA_THRESHOLD),
+    ))
+}
+
+/// The detailed hit box split into convex parts, ready for `CompoundHitBox`.
+#[pyfunction]
+pub fn calculate_hit_box_parts_detailed(
+    image_data: &[u8],
+    width: u32,
+    height: u32,
+    detail: Option<f32>,
+    threshold: Option<u8>,
+) -> PyResult<Vec<Vec<(f32, f32)>>> {
+    let points = calculate_hit_box_points_detailed(image_data, width, height, detail, threshold)?;
+    let parts = decompose_polygon_native(&points);
+    if parts.is_empty() {
+        return Ok(vec![points]);
+    }
+    Ok(parts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build RGBA data from rows of '#' (opaque) and '.' (transparent), top row first.
+    fn image_from_rows(rows: &[&str]) -> (Vec<u8>, u32, u32) {
+        let mut data = Vec::new();
+        for row in rows {
+            for c in row.chars() {
+                let alpha = if c == '#' { 255 } else { 0 };
+                data.extend_from_slice(&[255, 255, 255, alpha]);
+            }
+        }
+        (data, rows[0].len() as u32, rows.len() as u32)
+    }
+
+    #[test]
+    fn test_invalid_data_length() {
+        assert!(AlphaImage::new(&[0, 0, 0], 1, 1).is_err());
+    }
+
+    #[test]
+    fn test_bounding() {
+        let (data, width, height) = image_from_rows(&["....", "...."]);
+        let points = calculate_hit_box_points_bounding(&data, width, height).unwrap();
+        assert_eq!(
+            points,
+            vec![(-2.0, -1.0), (2.0, -1.0), (2.0, 1.0), (-2.0, 1.0)]
+        );
+    }
+
+    #[test]
+    fn test_simple_empty_image() {
+        let (data, width, height) = image_from_rows(&["....", "...."]);
+        let points = calculate_hit_box_points_simple(&data, width, height).unwrap();
+        assert_eq!(
+            points,
+            vec![(-2.0, -1.0), (2.0, -1.0), (2.0, 1.0), (-2.0, 1.0)]
+        );
+    }
+
+    #[test]
+    fn test_simple_full_image() {
+        let (data, width, height) = image_from_rows(&["####", "####"]);
+        let points = calculate_hit_box_points_simple(&data, width, height).unwrap();
+        assert_eq!(
+            points,
+            vec![(-2.0, -1.0), (2.0, -1.0), (2.0, 1.0), (-2.0, 1.0)]
+        );
+    }
+
+    #[test]
+    fn test_simple_trimmed_and_cut_corners() {
+        let (data, width, height) = image_from_rows(&[
+            "......", //
+            "..##..", //
+            ".####.", //
+            "..##..", //
+            "......", //
+        ]);
+        let points = calculate_hit_box_points_simple(&data, width, height).unwrap();
+        assert_eq!(
+            points,
+            vec![
+                (-2.0, -0.5),
+                (-1.0, -1.5),
+                (1.0, -1.5),
+                (2.0, -0.5),
+                (2.0, 0.5),
+                (1.0, 1.5),
+                (-1.0, 1.5),
+                (-2.0, 0.5),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_trace_square() {
+        let (data, width, height) = image_from_rows(&["....", ".##.", ".##.", "...."]);
+        let image = AlphaImage::new(&data, width, height).unwrap();
+        let contours = trace_alpha_contours_native(&image, 127);
+        assert_eq!(contours.len(), 1);
+        // Eight crossings, each about half way between a pixel center inside and outside
+        assert_eq!(contours[0].len(), 8);
+        assert!(get_polygon_signed_area_native(&contours[0]) > 0.0);
+        for point in &contours[0] {
+            assert!(
+                point.0.abs() <= 1.01 && point.1.abs() <= 1.01,
+                "{:?}",
+                point
+            );
+        }
+    }
+
+    #[test]
+    fn test_trace_hole_and_saddle() {
+        let (data, width, height) = image_from_rows(&[
+            ".......", //
+            ".#####.", //
+            ".#...#.", //
+            ".#####.", //
+            ".......", //
+            ".#.....", //
+            "..#....", //
+            ".......", //
+        ]);
+        let image = AlphaImage::new(&data, width, height).unwrap();
+        let contours = trace_alpha_contours_native(&image, 127);
+        let mut windings: Vec<bool> = contours
+            .iter()
+            .map(|contour| get_polygon_signed_area_native(contour) > 0.0)
+            .collect();
+        windings.sort();
+        // The hole, the ring outline and the diagonal pixels joined at their corner
+        assert_eq!(windings, vec![false, true, true]);
+    }
+
+    #[test]
+    fn test_detailed_simplifies_outline() {
+        let rows: Vec<String> = (0..32)
+            .map(|y| {
+                (0..32)
+                    .map(|x| {
+                        if (4..28).contains(&x) && (8..24).contains(&y) {
+                            '#'
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        let rows: Vec<&str> = rows.iter().map(|row| row.as_str()).collect();
+        let (data, width, height) = image_from_rows(&rows);
+        let points = calculate_hit_box_points_detailed(&data, width, height, None, None).unwrap();
+        assert!(points.len() >= 4 && points.len() <= 8, "{:?}", points);
+        let area = get_polygon_signed_area_native(&points);
+        assert!(area > 0.0);
+        assert!((area - 24.0 * 16.0).abs() < 24.0, "{}", area);
+
+        let parts = calculate_hit_box_parts_detailed(&data, width, height, None, None).unwrap();
+        assert_eq!(parts.len(), 1);
+    }
+
+    #[test]
+    fn test_detailed_empty_image() {
+        let (data, width, height) = image_from_rows(&["..", ".."]);
+        let points = calculate_hit_box_points_detailed(&data, width, height, None, None).unwrap();
+        assert_eq!(
+            points,
+            vec![(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+        );
+    }
+}
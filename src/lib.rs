@@ -1,3 +1,6 @@
+// pyo3 0.20's macros expand to impls that newer compilers flag as non-local.
+#![allow(non_local_definitions)]
+
 use pyo3::prelude::*;
 
 mod hitbox;
@@ -12,11 +15,46 @@ pub use geometry::*;
 mod sprite_list;
 pub use sprite_list::*;
 
+mod spatial_hash;
+pub use spatial_hash::*;
+
+mod shape;
+pub use shape::*;
+
+mod decomposition;
+pub use decomposition::*;
+
+mod hitbox_algorithms;
+pub use hitbox_algorithms::*;
+
+mod point_set;
+pub use point_set::*;
+
+mod paths;
+pub use paths::*;
+
+mod navmesh;
+pub use navmesh::*;
+
 /// A Python module implemented in Rust.
 #[pymodule]
 fn arcade_accelerate(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<hitbox::HitBox>()?;
     m.add_class::<hitbox::RotatableHitBox>()?;
+    m.add_class::<hitbox::CircleHitBox>()?;
+    m.add_class::<hitbox::CapsuleHitBox>()?;
+    m.add_class::<hitbox::CompoundHitBox>()?;
+    m.add_class::<point_set::PointSet>()?;
+    m.add_class::<point_set::PointSetCache>()?;
+    m.add_class::<point_set::PointBuffer>()?;
+    m.add_class::<spatial_hash::SpatialHash>()?;
+    m.add_class::<paths::AStarBarrierList>()?;
+    m.add_class::<paths::FlowField>()?;
+    m.add_class::<navmesh::NavMesh>()?;
+    m.add_class::<geometry::Penetration>()?;
+    m.add_class::<geometry::TimeOfImpact>()?;
+    m.add_class::<geometry::RayHit>()?;
+    m.add_function(wrap_pyfunction!(hitbox::update_hit_box_transforms, m)?)?;
     m.add_function(wrap_pyfunction!(math::rotate_point, m)?)?;
     m.add_function(wrap_pyfunction!(math::clamp, m)?)?;
     m.add_function(wrap_pyfunction!(math::lerp, m)?)?;
@@ -35,9 +73,53 @@ fn arcade_accelerate(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(math::rand_vec_magnitude, m)?)?;
     m.add_function(wrap_pyfunction!(geometry::are_polygons_intersecting, m)?)?;
     m.add_function(wrap_pyfunction!(geometry::is_point_in_polygon, m)?)?;
+    m.add_function(wrap_pyfunction!(geometry::get_polygon_signed_area, m)?)?;
+    m.add_function(wrap_pyfunction!(geometry::get_polygon_centroid, m)?)?;
+    m.add_function(wrap_pyfunction!(geometry::get_polygon_perimeter, m)?)?;
+    m.add_function(wrap_pyfunction!(geometry::is_polygon_clockwise, m)?)?;
+    m.add_function(wrap_pyfunction!(geometry::normalize_polygon_winding, m)?)?;
+    m.add_function(wrap_pyfunction!(geometry::is_polygon_convex, m)?)?;
+    m.add_function(wrap_pyfunction!(geometry::get_convex_hull, m)?)?;
+    m.add_function(wrap_pyfunction!(geometry::are_circles_intersecting, m)?)?;
+    m.add_function(wrap_pyfunction!(
+        geometry::is_circle_intersecting_polygon,
+        m
+    )?)?;
+    m.add_function(wrap_pyfunction!(geometry::are_capsules_intersecting, m)?)?;
+    m.add_function(wrap_pyfunction!(
+        geometry::is_capsule_intersecting_polygon,
+        m
+    )?)?;
+    m.add_function(wrap_pyfunction!(geometry::get_polygons_penetration, m)?)?;
+    m.add_function(wrap_pyfunction!(geometry::get_polygons_time_of_impact, m)?)?;
+    m.add_function(wrap_pyfunction!(geometry::cast_ray_against_polygon, m)?)?;
+    m.add_function(wrap_pyfunction!(decomposition::decompose_polygon, m)?)?;
+    m.add_function(wrap_pyfunction!(geometry::simplify_polyline_rdp, m)?)?;
+    m.add_function(wrap_pyfunction!(geometry::simplify_polygon_rdp, m)?)?;
+    m.add_function(wrap_pyfunction!(geometry::simplify_polyline_vw, m)?)?;
+    m.add_function(wrap_pyfunction!(geometry::simplify_polygon_vw, m)?)?;
+    m.add_function(wrap_pyfunction!(geometry::remove_collinear_points, m)?)?;
+    m.add_function(wrap_pyfunction!(geometry::slide_vector, m)?)?;
+    m.add_function(wrap_pyfunction!(geometry::reflect_vector, m)?)?;
     m.add_function(wrap_pyfunction!(geometry::is_point_in_box, m)?)?;
     m.add_function(wrap_pyfunction!(geometry::get_triangle_orientation, m)?)?;
     m.add_function(wrap_pyfunction!(geometry::are_lines_intersecting, m)?)?;
+    m.add_function(wrap_pyfunction!(
+        hitbox_algorithms::calculate_hit_box_points_simple,
+        m
+    )?)?;
+    m.add_function(wrap_pyfunction!(
+        hitbox_algorithms::calculate_hit_box_points_bounding,
+        m
+    )?)?;
+    m.add_function(wrap_pyfunction!(
+        hitbox_algorithms::calculate_hit_box_points_detailed,
+        m
+    )?)?;
+    m.add_function(wrap_pyfunction!(
+        hitbox_algorithms::calculate_hit_box_parts_detailed,
+        m
+    )?)?;
     m.add_function(wrap_pyfunction!(
         sprite_list::check_for_collision_with_list,
         m
@@ -46,6 +128,12 @@ fn arcade_accelerate(_py: Python, m: &PyModule) -> PyResult<()> {
         sprite_list::check_for_collision_with_lists,
         m
     )?)?;
+    m.add_function(wrap_pyfunction!(sprite_list::get_time_of_impact, m)?)?;
+    m.add_function(wrap_pyfunction!(sprite_list::cast_sprite_against_list, m)?)?;
+    m.add_function(wrap_pyfunction!(sprite_list::cast_ray_against_list, m)?)?;
+    m.add_function(wrap_pyfunction!(sprite_list::cast_ray_against_list_all, m)?)?;
+    m.add_function(wrap_pyfunction!(sprite_list::has_line_of_sight, m)?)?;
+    m.add_function(wrap_pyfunction!(paths::astar_calculate_path, m)?)?;
 
     Ok(())
 }
@@ -222,8 +222,8 @@ impl _Vec2 {
             y: (self.y * cosine) + (self.x * sine),
         }
     }
-    fn __repr__(&self) {
-        format!("Vec2({},{})", self.x, self.y);
+    fn __repr__(&self) -> String {
+        format!("Vec2({},{})", self.x, self.y)
     }
 }
 
@@ -0,0 +1,721 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+use pyo3::exceptions::PyValueError;
+use pyo3::intern;
+use pyo3::prelude::*;
+
+use crate::geometry::{
+    cross, get_convex_hull_native, get_segments_intersection_point, is_polygon_convex_native,
+    remove_collinear_points_native, Aabb,
+};
+use crate::hitbox::ExtractedHitBox;
+
+/// Relative tolerance below which three points count as lying on a line.
+const COLLINEAR_TOLERANCE: f32 = 1.0e-5;
+
+/// The left and right ends of a portal, as seen while walking through it.
+type PortalEnds = ((f32, f32), (f32, f32));
+
+/// A straight line across one slab, as its heights at the slab's left and right edges.
+type SlabLine = (f32, f32);
+
+/// A convex piece of free space between two lines across a slab.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Trapezoid {
+    bottom: SlabLine,
+    top: SlabLine,
+}
+
+/// Where two walkable polygons meet, always a vertical segment.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Portal {
+    /// The polygon on the other side
+    pub to: usize,
+    pub x: f32,
+    pub bottom: f32,
+    pub top: f32,
+}
+
+/// Convex hulls of the obstacles, grown by `padding` in every direction so paths keep
+/// clear of them. Obstacles without any area are left out.
+pub fn get_obstacle_hulls(obstacles: &[Vec<(f32, f32)>], padding: f32) -> Vec<Vec<(f32, f32)>> {
+    obstacles
+        .iter()
+        .map(|obstacle| {
+            if padding > 0.0 {
+                let corners = [
+                    (-padding, -padding),
+                    (padding, -padding),
+                    (padding, padding),
+                    (-padding, padding),
+                ];
+                let grown: Vec<(f32, f32)> = obstacle
+                    .iter()
+                    .flat_map(|point| {
+                        corners
+                            .iter()
+                            .map(move |corner| (point.0 + corner.0, point.1 + corner.1))
+                    })
+                    .collect();
+                get_convex_hull_native(&grown)
+            } else {
+                get_convex_hull_native(obstacle)
+            }
+        })
+        .filter(|hull| hull.len() >= 3)
+        .collect()
+}
+
+fn get_edges(polygon: &[(f32, f32)]) -> impl Iterator<Item = ((f32, f32), (f32, f32))> + '_ {
+    (0..polygon.len()).map(move |i| (polygon[i], polygon[(i + 1) % polygon.len()]))
+}
+
+/// The height of the line through `a` and `b` at `x`.
+fn get_line_height(a: (f32, f32), b: (f32, f32), x: f32) -> f32 {
+    a.1 + (b.1 - a.1) * (x - a.0) / (b.0 - a.0)
+}
+
+/// The x coordinates splitting the bounds into slabs where no obstacle has a vertex and
+/// no obstacle edge crosses another edge or the top or bottom of the bounds, so every
+/// boundary keeps the same order from one side of a slab to the other.
+fn get_slab_breaks(bounds: &Aabb, hulls: &[Vec<(f32, f32)>]) -> Vec<f32> {
+    let mut breaks = vec![bounds.left, bounds.right];
+    let top_edge = ((bounds.left, bounds.top), (bounds.right, bounds.top));
+    let bottom_edge = ((bounds.left, bounds.bottom), (bounds.right, bounds.bottom));
+    for hull in hulls {
+        breaks.extend(hull.iter().map(|point| point.0));
+        for edge in get_edges(hull) {
+            for bound in [top_edge, bottom_edge] {
+                if let Some(point) =
+                    get_segments_intersection_point(edge.0, edge.1, bound.0, bound.1)
+                {
+                    breaks.push(point.0);
+                }
+            }
+        }
+    }
+    let hull_bounds: Vec<Aabb> = hulls.iter().map(|hull| Aabb::from_points(hull)).collect();
+    for i in 0..hulls.len() {
+        for j in (i + 1)..hulls.len() {
+            if !hull_bounds[i].intersects(&hull_bounds[j]) {
+                continue;
+            }
+            for edge_a in get_edges(&hulls[i]) {
+                for edge_b in get_edges(&hulls[j]) {
+                    if let Some(point) =
+                        get_segments_intersection_point(edge_a.0, edge_a.1, edge_b.0, edge_b.1)
+                    {
+                        breaks.push(point.0);
+                    }
+                }
+            }
+        }
+    }
+    breaks.retain(|x| *x >= bounds.left && *x <= bounds.right);
+    breaks.sort_by(f32::total_cmp);
+    breaks.dedup();
+    breaks
+}
+
+/// The lines bounding a convex obstacle from below and above across the slab from `x0`
+/// to `x1`, or `None` if it is not in the slab.
+fn get_obstacle_span(hull: &[(f32, f32)], x0: f32, x1: f32) -> Option<(SlabLine, SlabLine)> {
+    let center = (x0 + x1) / 2.0;
+    let mut crossing = get_edges(hull)
+        .filter(|(a, b)| a.0 != b.0 && a.0.min(b.0) <= center && a.0.max(b.0) >= center);
+    let (a, b) = crossing.next()?;
+    let (c, d) = crossing.next()?;
+    let first = (get_line_height(a, b, x0), get_line_height(a, b, x1));
+    let second = (get_line_height(c, d, x0), get_line_height(c, d, x1));
+    if get_line_height(a, b, center) <= get_line_height(c, d, center) {
+        Some((first, second))
+    } else {
+        Some((second, first))
+    }
+}
+
+/// The free space of one slab, from the bottom up, between the merged obstacle spans.
+fn get_slab_trapezoids(
+    bounds: &Aabb,
+    hulls: &[Vec<(f32, f32)>],
+    x0: f32,
+    x1: f32,
+) -> Vec<Trapezoid> {
+    let clamp = |line: SlabLine| {
+        (
+            line.0.clamp(bounds.bottom, bounds.top),
+            line.1.clamp(bounds.bottom, bounds.top),
+        )
+    };
+    let center = |line: SlabLine| (line.0 + line.1) / 2.0;
+
+    let mut spans: Vec<(SlabLine, SlabLine)> = hulls
+        .iter()
+        .filter_map(|hull| get_obstacle_span(hull, x0, x1))
+        .map(|(lower, upper)| (clamp(lower), clamp(upper)))
+        .collect();
+    spans.sort_by(|a, b| center(a.0).total_cmp(&center(b.0)));
+
+    let mut trapezoids = Vec::new();
+    let mut floor = (bounds.bottom, bounds.bottom);
+    let mut add_gap = |floor: SlabLine, ceiling: SlabLine| {
+        let ceiling = (ceiling.0.max(floor.0), ceiling.1.max(floor.1));
+        if ceiling.0 - floor.0 + ceiling.1 - floor.1 > 0.0 {
+            trapezoids.push(Trapezoid {
+                bottom: floor,
+                top: ceiling,
+            });
+        }
+    };
+    for (lower, upper) in spans {
+        if center(lower) > center(floor) {
+            add_gap(floor, lower);
+            floor = upper;
+        } else {
+            // Overlapping the spans below, so only the top of the union matters
+            floor = (floor.0.max(upper.0), floor.1.max(upper.1));
+        }
+    }
+    add_gap(floor, (bounds.top, bounds.top));
+    trapezoids
+}
+
+/// Add a point to the end of a chain, replacing the last point if the three would lie
+/// on a line.
+fn extend_chain(chain: &mut Vec<(f32, f32)>, point: (f32, f32)) {
+    if let [.., a, b] = chain[..] {
+        let ab = (b.0 - a.0, b.1 - a.1);
+        let bc = (point.0 - b.0, point.1 - b.1);
+        if cross(a, b, point).abs() <= COLLINEAR_TOLERANCE * ab.0.hypot(ab.1) * bc.0.hypot(bc.1) {
+            chain.pop();
+        }
+    }
+    chain.push(point);
+}
+
+/// A run of trapezoids from neighbouring slabs, joined into one convex polygon.
+#[derive(Clone, Debug)]
+struct Chain {
+    bottom: Vec<(f32, f32)>,
+    top: Vec<(f32, f32)>,
+}
+
+impl Chain {
+    fn new(x0: f32, x1: f32, trapezoid: &Trapezoid) -> Chain {
+        Chain {
+            bottom: vec![(x0, trapezoid.bottom.0), (x1, trapezoid.bottom.1)],
+            top: vec![(x0, trapezoid.top.0), (x1, trapezoid.top.1)],
+        }
+    }
+
+    fn extend(&mut self, x: f32, trapezoid: &Trapezoid) {
+        extend_chain(&mut self.bottom, (x, trapezoid.bottom.1));
+        extend_chain(&mut self.top, (x, trapezoid.top.1));
+    }
+
+    /// The outline counter-clockwise from the bottom left.
+    fn polygon(&self) -> Vec<(f32, f32)> {
+        let outline: Vec<(f32, f32)> = self
+            .bottom
+            .iter()
+            .chain(self.top.iter().rev())
+            .copied()
+            .collect();
+        remove_collinear_points_native(&outline, 0.0)
+    }
+}
+
+/// Convex walkable polygons covering the free space of a rectangle, and the portals
+/// joining them, for finding paths around obstacles.
+#[pyclass]
+pub struct NavMesh {
+    pub polygons: Vec<Vec<(f32, f32)>>,
+    pub portals: Vec<Vec<Portal>>,
+}
+
+impl NavMesh {
+    /// Cut the obstacles' convex hulls out of `bounds`, see `get_obstacle_hulls`.
+    ///
+    /// The free space is split into trapezoids along every obstacle vertex and crossing,
+    /// and then neighbouring trapezoids are joined from left to right for as long as
+    /// they stay convex.
+    pub fn from_obstacles_native(
+        bounds: &Aabb,
+        obstacles: &[Vec<(f32, f32)>],
+        padding: f32,
+    ) -> NavMesh {
+        let hulls = get_obstacle_hulls(obstacles, padding);
+        let breaks = get_slab_breaks(bounds, &hulls);
+
+        let mut chains: Vec<Chain> = Vec::new();
+        // The chain of each trapezoid in the previous slab and the shared portals
+        let mut previous: Vec<(Trapezoid, usize)> = Vec::new();
+        let mut shared: HashMap<(usize, usize), (f32, f32, f32)> = HashMap::new();
+        for slab in breaks.windows(2) {
+            let (x0, x1) = (slab[0], slab[1]);
+            let mut current: Vec<(Trapezoid, usize)> = Vec::new();
+            for trapezoid in get_slab_trapezoids(bounds, &hulls, x0, x1) {
+                let touching: Vec<&(Trapezoid, usize)> = previous
+                    .iter()
+                    .filter(|(left, _)| {
+                        left.top.1.min(trapezoid.top.0) > left.bottom.1.max(trapezoid.bottom.0)
+                    })
+                    .collect();
+
+                // Join the chain on the left if the trapezoids share a whole edge
+                let mut chain = None;
+                if let [(left, index)] = touching[..] {
+                    let is_whole_edge =
+                        left.bottom.1 == trapezoid.bottom.0 && left.top.1 == trapezoid.top.0;
+                    let is_chain_end = chains[*index].bottom.last().unwrap().0 == x0;
+                    if is_whole_edge && is_chain_end {
+                        let mut joined = chains[*index].clone();
+                        joined.extend(x1, &trapezoid);
+                        if is_polygon_convex_native(&joined.polygon()) {
+                            chains[*index] = joined;
+                            chain = Some(*index);
+                        }
+                    }
+                }
+                let chain = chain.unwrap_or_else(|| {
+                    chains.push(Chain::new(x0, x1, &trapezoid));
+                    chains.len() - 1
+                });
+
+                for (left, index) in touching {
+                    if *index == chain {
+                        continue;
+                    }
+                    let bottom = left.bottom.1.max(trapezoid.bottom.0);
+                    let top = left.top.1.min(trapezoid.top.0);
+                    shared
+                        .entry((*index, chain))
+                        .and_modify(|portal| {
+                            portal.1 = portal.1.min(bottom);
+                            portal.2 = portal.2.max(top);
+                        })
+                        .or_insert((x0, bottom, top));
+                }
+                current.push((trapezoid, chain));
+            }
+            previous = current;
+        }
+
+        let mut portals: Vec<Vec<Portal>> = vec![Vec::new(); chains.len()];
+        let mut shared: Vec<_> = shared.into_iter().collect();
+        shared.sort_by_key(|(key, _)| *key);
+        for ((left, right), (x, bottom, top)) in shared {
+            portals[left].push(Portal {
+                to: right,
+                x,
+                bottom,
+                top,
+            });
+            portals[right].push(Portal {
+                to: left,
+                x,
+                bottom,
+                top,
+            });
+        }
+        NavMesh {
+            polygons: chains.iter().map(Chain::polygon).collect(),
+            portals,
+        }
+    }
+
+    /// The polygon containing a point, counting its edges as inside.
+    pub fn get_polygon_index_native(&self, point: (f32, f32)) -> Option<usize> {
+        self.polygons.iter().position(|polygon| {
+            let bounds = Aabb::from_points(polygon);
+            bounds.contains_point(point)
+                && get_edges(polygon).all(|(a, b)| {
+                    cross(a, b, point)
+                        >= -COLLINEAR_TOLERANCE * (b.0 - a.0).hypot(b.1 - a.1).max(1.0)
+                })
+        })
+    }
+
+    /// The polygons and portals to cross on the way from `start` to `end`, found with A*
+    /// moving between the nearest points of each portal.
+    fn find_corridor(
+        &self,
+        start: (f32, f32),
+        start_polygon: usize,
+        end: (f32, f32),
+        end_polygon: usize,
+    ) -> Option<Vec<(usize, Portal)>> {
+        let distance = |a: (f32, f32), b: (f32, f32)| (b.0 - a.0).hypot(b.1 - a.1);
+        let mut costs = vec![f32::INFINITY; self.polygons.len()];
+        let mut entries = vec![start; self.polygons.len()];
+        let mut came_from: Vec<Option<(usize, Portal)>> = vec![None; self.polygons.len()];
+        costs[start_polygon] = 0.0;
+        let mut open = BinaryHeap::from([OpenPolygon {
+            estimate: distance(start, end),
+            cost: 0.0,
+            polygon: start_polygon,
+        }]);
+
+        while let Some(OpenPolygon { cost, polygon, .. }) = open.pop() {
+            if polygon == end_polygon {
+                let mut corridor = Vec::new();
+                let mut current = polygon;
+                while let Some((previous, portal)) = came_from[current] {
+                    corridor.push((previous, portal));
+                    current = previous;
+                }
+                corridor.reverse();
+                return Some(corridor);
+            }
+            if costs[polygon] < cost {
+                continue;
+            }
+            let entry = entries[polygon];
+            for portal in &self.portals[polygon] {
+                let point = (portal.x, entry.1.clamp(portal.bottom, portal.top));
+                let next_cost = cost + distance(entry, point);
+                if next_cost >= costs[portal.to] {
+                    continue;
+                }
+                costs[portal.to] = next_cost;
+                entries[portal.to] = point;
+                came_from[portal.to] = Some((polygon, *portal));
+                open.push(OpenPolygon {
+                    estimate: next_cost + distance(point, end),
+                    cost: next_cost,
+                    polygon: portal.to,
+                });
+            }
+        }
+        None
+    }
+
+    /// The shortest path of points from `start` to `end` through the polygons, or
+    /// `None` if either point is outside them or there is no way between them.
+    pub fn find_path_native(&self, start: (f32, f32), end: (f32, f32)) -> Option<Vec<(f32, f32)>> {
+        let start_polygon = self.get_polygon_index_native(start)?;
+        let end_polygon = self.get_polygon_index_native(end)?;
+        let corridor = self.find_corridor(start, start_polygon, end, end_polygon)?;
+
+        // The start and end count as portals with no width
+        let mut portals: Vec<PortalEnds> = vec![(start, start)];
+        for (polygon, portal) in corridor {
+            let bottom = (portal.x, portal.bottom);
+            let top = (portal.x, portal.top);
+            let is_heading_right = self.polygons[polygon]
+                .iter()
+                .any(|point| point.0 < portal.x);
+            portals.push(if is_heading_right {
+                (top, bottom)
+            } else {
+                (bottom, top)
+            });
+        }
+        portals.push((end, end));
+        Some(pull_string(&portals))
+    }
+}
+
+/// The simple stupid funnel algorithm: pull a string through `(left, right)` portals,
+/// the first being the start and the last the end, keeping only the corners it wraps.
+pub fn pull_string(portals: &[PortalEnds]) -> Vec<(f32, f32)> {
+    let start = portals[0].0;
+    let end = portals[portals.len() - 1].0;
+    let mut path = vec![start];
+    let (mut apex, mut left, mut right) = (start, start, start);
+    let (mut left_index, mut right_index) = (0, 0);
+
+    let mut i = 1;
+    while i < portals.len() {
+        let (portal_left, portal_right) = portals[i];
+
+        // Narrow the funnel from the right, unless that crosses over the left side
+        if cross(apex, right, portal_right) >= 0.0 {
+            if apex == right || apex == left || cross(apex, left, portal_right) < 0.0 {
+                right = portal_right;
+                right_index = i;
+            } else {
+                if path.last() != Some(&left) {
+                    path.push(left);
+                }
+                // The left corner becomes the new apex and the scan restarts after it
+                apex = left;
+                (right, right_index) = (left, left_index);
+                i = left_index + 1;
+                continue;
+            }
+        }
+
+        // And the same from the left
+        if cross(apex, left, portal_left) <= 0.0 {
+            if apex == left || apex == right || cross(apex, right, portal_left) > 0.0 {
+                left = portal_left;
+                left_index = i;
+            } else {
+                if path.last() != Some(&right) {
+                    path.push(right);
+                }
+                apex = right;
+                (left, left_index) = (right, right_index);
+                i = right_index + 1;
+                continue;
+            }
+        }
+        i += 1;
+    }
+    if path.last() != Some(&end) {
+        path.push(end);
+    }
+    path
+}
+
+/// A polygon in the A* queue, ordered so the heap pops the lowest estimate first.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct OpenPolygon {
+    estimate: f32,
+    cost: f32,
+    polygon: usize,
+}
+
+impl Eq for OpenPolygon {}
+
+impl Ord for OpenPolygon {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .estimate
+            .total_cmp(&self.estimate)
+            .then_with(|| other.polygon.cmp(&self.polygon))
+    }
+}
+
+impl PartialOrd for OpenPolygon {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn get_bounds(left: f32, right: f32, bottom: f32, top: f32) -> PyResult<Aabb> {
+    if left >= right || bottom >= top {
+        return Err(PyValueError::new_err(
+            "navigation mesh bounds must have a positive width and height",
+        ));
+    }
+    Ok(Aabb {
+        left,
+        right,
+        bottom,
+        top,
+    })
+}
+
+#[pymethods]
+impl NavMesh {
+    /// Build a navigation mesh covering the rectangle from `left` to `right` and
+    /// `bottom` to `top`, minus the hit boxes of the blocking sprites.
+    ///
+    /// Hit boxes are cut out as their convex hulls, or the hulls of each part of a
+    /// compound hit box, grown by `padding` so paths keep that far from them.
+    #[new]
+    fn new(
+        py: Python<'_>,
+        blocking_sprites: &PyAny,
+        left: f32,
+        right: f32,
+        bottom: f32,
+        top: f32,
+        padding: Option<f32>,
+    ) -> PyResult<NavMesh> {
+        let bounds = get_bounds(left, right, bottom, top)?;
+        let mut obstacles: Vec<Vec<(f32, f32)>> = Vec::new();
+        for sprite in blocking_sprites.iter()? {
+            let hitbox_object = sprite?.getattr(intern!(py, "_hit_box"))?;
+            let mut hitbox = ExtractedHitBox::from_hit_box(py, hitbox_object)?;
+            obstacles.extend(hitbox.polygons().iter().map(|polygon| polygon.to_vec()));
+        }
+        Ok(NavMesh::from_obstacles_native(
+            &bounds,
+            &obstacles,
+            padding.unwrap_or(0.0),
+        ))
+    }
+
+    /// Build a navigation mesh around obstacle polygons instead of sprites.
+    #[staticmethod]
+    pub fn from_obstacles(
+        obstacles: Vec<Vec<(f32, f32)>>,
+        left: f32,
+        right: f32,
+        bottom: f32,
+        top: f32,
+        padding: Option<f32>,
+    ) -> PyResult<NavMesh> {
+        let bounds = get_bounds(left, right, bottom, top)?;
+        Ok(NavMesh::from_obstacles_native(
+            &bounds,
+            &obstacles,
+            padding.unwrap_or(0.0),
+        ))
+    }
+
+    /// The walkable polygons, each convex and counter-clockwise.
+    #[getter]
+    pub fn polygons(&self) -> Vec<Vec<(f32, f32)>> {
+        self.polygons.clone()
+    }
+
+    pub fn __len__(&self) -> usize {
+        self.polygons.len()
+    }
+
+    /// The index in `polygons` of the polygon containing a point, or `None` if the
+    /// point is not walkable.
+    pub fn get_polygon_index(&self, point: (f32, f32)) -> Option<usize> {
+        self.get_polygon_index_native(point)
+    }
+
+    /// The points of a smooth path from `start` to `end`, both included, turning only
+    /// at obstacle corners, or `None` if there is no way between them.
+    pub fn find_path(&self, start: (f32, f32), end: (f32, f32)) -> Option<Vec<(f32, f32)>> {
+        self.find_path_native(start, end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::get_polygon_signed_area_native;
+
+    fn square(left: f32, bottom: f32, size: f32) -> Vec<(f32, f32)> {
+        vec![
+            (left, bottom),
+            (left + size, bottom),
+            (left + size, bottom + size),
+            (left, bottom + size),
+        ]
+    }
+
+    fn get_total_area(navmesh: &NavMesh) -> f32 {
+        navmesh
+            .polygons
+            .iter()
+            .map(|polygon| get_polygon_signed_area_native(polygon))
+            .sum()
+    }
+
+    fn get_path_length(path: &[(f32, f32)]) -> f32 {
+        path.windows(2)
+            .map(|step| (step[1].0 - step[0].0).hypot(step[1].1 - step[0].1))
+            .sum()
+    }
+
+    const BOUNDS: Aabb = Aabb {
+        left: 0.0,
+        right: 100.0,
+        bottom: 0.0,
+        top: 100.0,
+    };
+
+    #[test]
+    fn test_empty_navmesh() {
+        let navmesh = NavMesh::from_obstacles_native(&BOUNDS, &[], 0.0);
+        assert_eq!(navmesh.polygons, vec![square(0.0, 0.0, 100.0)]);
+        let path = navmesh.find_path_native((10.0, 10.0), (90.0, 60.0));
+        assert_eq!(path, Some(vec![(10.0, 10.0), (90.0, 60.0)]));
+    }
+
+    #[test]
+    fn test_navmesh_polygons() {
+        let obstacles = vec![square(40.0, 40.0, 20.0), square(-10.0, 80.0, 30.0)];
+        let navmesh = NavMesh::from_obstacles_native(&BOUNDS, &obstacles, 0.0);
+        assert!(navmesh
+            .polygons
+            .iter()
+            .all(|polygon| is_polygon_convex_native(polygon)));
+        // Everything but the middle square and the part of the other inside the bounds
+        assert!((get_total_area(&navmesh) - (10000.0 - 400.0 - 400.0)).abs() < 1.0e-2);
+        assert_eq!(navmesh.get_polygon_index_native((50.0, 50.0)), None);
+        assert_eq!(navmesh.get_polygon_index_native((150.0, 50.0)), None);
+        assert!(navmesh.get_polygon_index_native((30.0, 50.0)).is_some());
+    }
+
+    #[test]
+    fn test_navmesh_padding() {
+        let obstacles = vec![square(40.0, 40.0, 20.0)];
+        let navmesh = NavMesh::from_obstacles_native(&BOUNDS, &obstacles, 5.0);
+        assert!((get_total_area(&navmesh) - (10000.0 - 900.0)).abs() < 1.0e-2);
+        assert_eq!(navmesh.get_polygon_index_native((37.0, 50.0)), None);
+    }
+
+    #[test]
+    fn test_find_path_around_obstacle() {
+        let obstacles = vec![square(40.0, 40.0, 20.0)];
+        let navmesh = NavMesh::from_obstacles_native(&BOUNDS, &obstacles, 0.0);
+        let path = navmesh
+            .find_path_native((10.0, 55.0), (90.0, 55.0))
+            .unwrap();
+        // Over the top, turning only at the square's corners
+        assert_eq!(
+            path,
+            vec![(10.0, 55.0), (40.0, 60.0), (60.0, 60.0), (90.0, 55.0)]
+        );
+        assert!((get_path_length(&path) - (20.0 + 2.0 * 30.0_f32.hypot(5.0))).abs() < 1.0e-3);
+    }
+
+    #[test]
+    fn test_find_path_overlapping_obstacles() {
+        // Overlapping diamonds making one wall with a gap above it
+        let diamond =
+            |x: f32, y: f32| vec![(x, y - 20.0), (x + 20.0, y), (x, y + 20.0), (x - 20.0, y)];
+        let obstacles = vec![
+            diamond(50.0, 20.0),
+            diamond(50.0, 45.0),
+            diamond(50.0, 70.0),
+        ];
+        let navmesh = NavMesh::from_obstacles_native(&BOUNDS, &obstacles, 0.0);
+        assert!(navmesh
+            .polygons
+            .iter()
+            .all(|polygon| is_polygon_convex_native(polygon)));
+        let path = navmesh
+            .find_path_native((20.0, 10.0), (80.0, 10.0))
+            .unwrap();
+        // Around the widest corners of the top diamond
+        assert_eq!(
+            path,
+            vec![
+                (20.0, 10.0),
+                (30.0, 70.0),
+                (50.0, 90.0),
+                (70.0, 70.0),
+                (80.0, 10.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_path_blocked() {
+        let wall = vec![(45.0, -10.0), (55.0, -10.0), (55.0, 110.0), (45.0, 110.0)];
+        let navmesh = NavMesh::from_obstacles_native(&BOUNDS, &[wall], 0.0);
+        assert_eq!(navmesh.polygons.len(), 2);
+        assert_eq!(navmesh.find_path_native((10.0, 10.0), (90.0, 10.0)), None);
+        // Starting inside the wall or outside the bounds
+        assert_eq!(navmesh.find_path_native((50.0, 10.0), (10.0, 10.0)), None);
+        assert_eq!(navmesh.find_path_native((10.0, 10.0), (10.0, 150.0)), None);
+    }
+
+    #[test]
+    fn test_pull_string() {
+        // A corridor bending round a corner at (10, 10)
+        let portals = [
+            ((0.0, 5.0), (0.0, 5.0)),
+            ((10.0, 10.0), (10.0, 0.0)),
+            ((10.0, 10.0), (20.0, 10.0)),
+            ((15.0, 20.0), (15.0, 20.0)),
+        ];
+        assert_eq!(
+            pull_string(&portals),
+            vec![(0.0, 5.0), (10.0, 10.0), (15.0, 20.0)]
+        );
+    }
+}
@@ -0,0 +1,896 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::f32::consts::SQRT_2;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::intern;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PySet};
+
+use crate::geometry::Aabb;
+use crate::hitbox::ExtractedHitBox;
+use crate::point_set::{extract_shared_points, points_memoryview, SharedPoints};
+use crate::shape::Shape;
+
+/// Blocked cells of a grid covering the columns `left..=right` and rows `bottom..=top`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BarrierGrid {
+    pub left: i32,
+    pub right: i32,
+    pub bottom: i32,
+    pub top: i32,
+    blocked: Vec<bool>,
+}
+
+impl BarrierGrid {
+    pub fn new(left: i32, right: i32, bottom: i32, top: i32) -> BarrierGrid {
+        let width = (right - left + 1).max(0) as usize;
+        let height = (top - bottom + 1).max(0) as usize;
+        BarrierGrid {
+            left,
+            right,
+            bottom,
+            top,
+            blocked: vec![false; width * height],
+        }
+    }
+
+    pub fn contains(&self, cell: (i32, i32)) -> bool {
+        cell.0 >= self.left && cell.0 <= self.right && cell.1 >= self.bottom && cell.1 <= self.top
+    }
+
+    /// The number of cells in the grid.
+    pub fn len(&self) -> usize {
+        self.blocked.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocked.is_empty()
+    }
+
+    /// The position of a cell in row-major order, starting from the bottom left.
+    fn index(&self, cell: (i32, i32)) -> usize {
+        let width = self.right - self.left + 1;
+        ((cell.1 - self.bottom) * width + (cell.0 - self.left)) as usize
+    }
+
+    fn cell(&self, index: usize) -> (i32, i32) {
+        let width = (self.right - self.left + 1) as usize;
+        (
+            self.left + (index % width) as i32,
+            self.bottom + (index / width) as i32,
+        )
+    }
+
+    /// Cells outside the grid are never blocked.
+    pub fn is_blocked(&self, cell: (i32, i32)) -> bool {
+        self.contains(cell) && self.blocked[self.index(cell)]
+    }
+
+    /// Cells outside the grid are ignored.
+    pub fn set_blocked(&mut self, cell: (i32, i32), blocked: bool) {
+        if self.contains(cell) {
+            let index = self.index(cell);
+            self.blocked[index] = blocked;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.blocked.fill(false);
+    }
+
+    pub fn blocked_cells(&self) -> impl Iterator<Item = (i32, i32)> + '_ {
+        (self.bottom..=self.top)
+            .flat_map(move |y| (self.left..=self.right).map(move |x| (x, y)))
+            .filter(|cell| self.is_blocked(*cell))
+    }
+
+    /// Whether a step from `cell` by `offset` stays in the grid and off blocked cells.
+    /// Diagonal steps past the corner of a blocked or outside cell also need
+    /// `cut_corners`.
+    fn can_step(&self, cell: (i32, i32), offset: (i32, i32), cut_corners: bool) -> bool {
+        let next = (cell.0 + offset.0, cell.1 + offset.1);
+        if !self.contains(next) || self.is_blocked(next) {
+            return false;
+        }
+        if cut_corners || offset.0 == 0 || offset.1 == 0 {
+            return true;
+        }
+        let is_open = |cell: (i32, i32)| self.contains(cell) && !self.is_blocked(cell);
+        is_open((cell.0 + offset.0, cell.1)) && is_open((cell.0, cell.1 + offset.1))
+    }
+}
+
+/// How A* may move between grid cells.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GridMovement {
+    /// Only up, down, left and right
+    Orthogonal,
+    /// Diagonal steps too, including squeezing between two blocked cells that touch at
+    /// a corner, as in arcade
+    Diagonal,
+    /// Diagonal steps only when both cells beside the step are open
+    DiagonalNoCornerCutting,
+}
+
+impl GridMovement {
+    pub fn from_options(diagonal_movement: bool, cut_corners: bool) -> GridMovement {
+        match (diagonal_movement, cut_corners) {
+            (false, _) => GridMovement::Orthogonal,
+            (true, true) => GridMovement::Diagonal,
+            (true, false) => GridMovement::DiagonalNoCornerCutting,
+        }
+    }
+
+    fn cut_corners(&self) -> bool {
+        *self != GridMovement::DiagonalNoCornerCutting
+    }
+
+    fn offsets(&self) -> &'static [(i32, i32)] {
+        match self {
+            GridMovement::Orthogonal => &[(1, 0), (-1, 0), (0, 1), (0, -1)],
+            _ => &[
+                (1, 0),
+                (-1, 0),
+                (0, 1),
+                (0, -1),
+                (1, 1),
+                (-1, 1),
+                (1, -1),
+                (-1, -1),
+            ],
+        }
+    }
+
+    /// The cost of the cheapest path between two cells on an empty grid.
+    fn heuristic(&self, from: (i32, i32), to: (i32, i32)) -> f32 {
+        let dx = (from.0 - to.0).abs() as f32;
+        let dy = (from.1 - to.1).abs() as f32;
+        match self {
+            GridMovement::Orthogonal => dx + dy,
+            _ => dx.max(dy) + (SQRT_2 - 1.0) * dx.min(dy),
+        }
+    }
+}
+
+/// An open cell in the A* queue, ordered so the heap pops the lowest estimate first.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct OpenCell {
+    estimate: f32,
+    cost: f32,
+    cell: (i32, i32),
+}
+
+impl Eq for OpenCell {}
+
+impl Ord for OpenCell {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .estimate
+            .total_cmp(&self.estimate)
+            // Prefer cells further along on ties, which explores fewer cells
+            .then_with(|| self.cost.total_cmp(&other.cost))
+            .then_with(|| other.cell.cmp(&self.cell))
+    }
+}
+
+impl PartialOrd for OpenCell {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn get_step_length(offset: (i32, i32)) -> f32 {
+    if offset.0 == 0 || offset.1 == 0 {
+        1.0
+    } else {
+        SQRT_2
+    }
+}
+
+/// The shortest path of cells from `start` to `end`, both included, or `None` if the
+/// end can not be reached. Straight steps cost 1 and diagonal ones the square root of 2.
+///
+/// The start cell may be blocked or outside the grid, but every other cell on the path
+/// is inside the grid and open.
+pub fn astar_search_native(
+    grid: &BarrierGrid,
+    start: (i32, i32),
+    end: (i32, i32),
+    movement: GridMovement,
+) -> Option<Vec<(i32, i32)>> {
+    let cut_corners = movement.cut_corners();
+    let mut costs: HashMap<(i32, i32), f32> = HashMap::from([(start, 0.0)]);
+    let mut came_from: HashMap<(i32, i32), (i32, i32)> = HashMap::new();
+    let mut open = BinaryHeap::from([OpenCell {
+        estimate: movement.heuristic(start, end),
+        cost: 0.0,
+        cell: start,
+    }]);
+
+    while let Some(OpenCell { cost, cell, .. }) = open.pop() {
+        if cell == end {
+            let mut path = vec![cell];
+            let mut current = cell;
+            while let Some(previous) = came_from.get(&current) {
+                current = *previous;
+                path.push(current);
+            }
+            path.reverse();
+            return Some(path);
+        }
+        // A cheaper way to this cell was already explored
+        if costs.get(&cell).is_some_and(|best| *best < cost) {
+            continue;
+        }
+        for &offset in movement.offsets() {
+            if !grid.can_step(cell, offset, cut_corners) {
+                continue;
+            }
+            let next = (cell.0 + offset.0, cell.1 + offset.1);
+            let next_cost = cost + get_step_length(offset);
+            if costs.get(&next).is_some_and(|best| *best <= next_cost) {
+                continue;
+            }
+            costs.insert(next, next_cost);
+            came_from.insert(next, cell);
+            open.push(OpenCell {
+                estimate: next_cost + movement.heuristic(next, end),
+                cost: next_cost,
+                cell: next,
+            });
+        }
+    }
+    None
+}
+
+/// The grid cell holding a point, like arcade's `_collapse`.
+fn get_grid_cell(point: (f32, f32), grid_size: i32) -> (i32, i32) {
+    (
+        (point.0 / grid_size as f32).floor() as i32,
+        (point.1 / grid_size as f32).floor() as i32,
+    )
+}
+
+/// Translate polygons into `translated`, reusing its allocations.
+fn translate_polygons(
+    polygons: &[Vec<(f32, f32)>],
+    offset: (f32, f32),
+    translated: &mut [Vec<(f32, f32)>],
+) {
+    for (polygon, target) in polygons.iter().zip(translated.iter_mut()) {
+        target.clear();
+        target.extend(
+            polygon
+                .iter()
+                .map(|point| (point.0 + offset.0, point.1 + offset.1)),
+        );
+    }
+}
+
+/// The grid cells a moving sprite can not stand in without touching a blocking sprite,
+/// for `astar_calculate_path`.
+///
+/// Cells are blocked by placing the moving sprite's hit box at each cell's corner and
+/// testing it against the blocking sprites' hit boxes, the same as arcade does with
+/// `check_for_collision_with_list` but without moving the sprite. Only the cells near
+/// each blocking sprite are tested. Circles and capsules are tested as their polygon
+/// outlines when they belong to the moving sprite.
+///
+/// As in arcade, `barrier_list` is a plain set of blocked `(column, row)` cells that can
+/// be edited or replaced with another set. `astar_calculate_path` and `FlowField` read it on
+/// every call, ignoring cells outside the grid, and `recalculate` refills the same set.
+#[pyclass(module = "arcade.paths")]
+pub struct AStarBarrierList {
+    #[pyo3(get, set)]
+    pub moving_sprite: PyObject,
+    #[pyo3(get, set)]
+    pub blocking_sprites: PyObject,
+    #[pyo3(get)]
+    pub grid_size: i32,
+    #[pyo3(get)]
+    pub left: i32,
+    #[pyo3(get)]
+    pub right: i32,
+    #[pyo3(get)]
+    pub bottom: i32,
+    #[pyo3(get)]
+    pub top: i32,
+    #[pyo3(get, set)]
+    pub barrier_list: Py<PySet>,
+}
+
+impl AStarBarrierList {
+    /// The grid with the cells in `barrier_list` blocked.
+    pub fn get_grid(&self, py: Python<'_>) -> PyResult<BarrierGrid> {
+        let mut grid = BarrierGrid::new(self.left, self.right, self.bottom, self.top);
+        for cell in self.barrier_list.as_ref(py) {
+            grid.set_blocked(cell.extract()?, true);
+        }
+        Ok(grid)
+    }
+
+    /// The grid with the cells blocked by the blocking sprites.
+    fn compute_grid(&self, py: Python<'_>) -> PyResult<BarrierGrid> {
+        let mut grid = BarrierGrid::new(self.left, self.right, self.bottom, self.top);
+        let moving_sprite = self.moving_sprite.as_ref(py);
+        let position: (f32, f32) = moving_sprite.getattr(intern!(py, "position"))?.extract()?;
+
+        // Copied relative to the sprite, which also frees its hit box to be borrowed
+        // again if a blocking sprite shares it
+        let mut moving_hitbox =
+            ExtractedHitBox::from_hit_box(py, moving_sprite.getattr(intern!(py, "_hit_box"))?)?;
+        let moving_polygons: Vec<Vec<(f32, f32)>> = moving_hitbox
+            .polygons()
+            .iter()
+            .map(|polygon| {
+                polygon
+                    .iter()
+                    .map(|point| (point.0 - position.0, point.1 - position.1))
+                    .collect()
+            })
+            .collect();
+        drop(moving_hitbox);
+        let moving_bounds = Aabb::from_points(&moving_polygons.concat());
+        if moving_bounds.is_empty() {
+            return Ok(grid);
+        }
+        let mut translated = moving_polygons.clone();
+
+        let cell_size = self.grid_size as f32;
+        for blocking_sprite in self.blocking_sprites.as_ref(py).iter()? {
+            let blocking_sprite = blocking_sprite?;
+            if blocking_sprite.is(moving_sprite) {
+                continue;
+            }
+            let mut blocking_hitbox = ExtractedHitBox::from_hit_box(
+                py,
+                blocking_sprite.getattr(intern!(py, "_hit_box"))?,
+            )?;
+            let bounds = blocking_hitbox.native().get_bounds_native();
+            if bounds.is_empty() {
+                continue;
+            }
+            let first = (
+                ((bounds.left - moving_bounds.right) / cell_size).ceil() as i32,
+                ((bounds.bottom - moving_bounds.top) / cell_size).ceil() as i32,
+            );
+            let last = (
+                ((bounds.right - moving_bounds.left) / cell_size).floor() as i32,
+                ((bounds.top - moving_bounds.bottom) / cell_size).floor() as i32,
+            );
+            let shape = blocking_hitbox.shape();
+            for x in first.0.max(grid.left)..=last.0.min(grid.right) {
+                for y in first.1.max(grid.bottom)..=last.1.min(grid.top) {
+                    if grid.is_blocked((x, y)) {
+                        continue;
+                    }
+                    let offset = (x as f32 * cell_size, y as f32 * cell_size);
+                    translate_polygons(&moving_polygons, offset, &mut translated);
+                    if Shape::Compound(&translated).intersects(&shape) {
+                        grid.set_blocked((x, y), true);
+                    }
+                }
+            }
+        }
+        Ok(grid)
+    }
+}
+
+#[pymethods]
+impl AStarBarrierList {
+    #[new]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        py: Python<'_>,
+        moving_sprite: PyObject,
+        blocking_sprites: PyObject,
+        grid_size: i32,
+        left: f32,
+        right: f32,
+        bottom: f32,
+        top: f32,
+    ) -> PyResult<AStarBarrierList> {
+        if grid_size <= 0 {
+            return Err(PyValueError::new_err("grid_size must be greater than zero"));
+        }
+        let (left, bottom) = get_grid_cell((left, bottom), grid_size);
+        let (right, top) = get_grid_cell((right, top), grid_size);
+        let barrier_list = AStarBarrierList {
+            moving_sprite,
+            blocking_sprites,
+            grid_size,
+            left,
+            right,
+            bottom,
+            top,
+            barrier_list: PySet::empty(py)?.into(),
+        };
+        barrier_list.recalculate(py)?;
+        Ok(barrier_list)
+    }
+
+    /// Whether `barrier_list` blocks a cell inside the grid.
+    pub fn is_blocked(&self, py: Python<'_>, cell: (i32, i32)) -> PyResult<bool> {
+        let inside = cell.0 >= self.left
+            && cell.0 <= self.right
+            && cell.1 >= self.bottom
+            && cell.1 <= self.top;
+        Ok(inside && self.barrier_list.as_ref(py).contains(cell)?)
+    }
+
+    /// Test every cell again, after the blocking sprites or the moving sprite's hit box
+    /// changed.
+    pub fn recalculate(&self, py: Python<'_>) -> PyResult<()> {
+        let grid = self.compute_grid(py)?;
+        let barrier_list = self.barrier_list.as_ref(py);
+        barrier_list.clear();
+        for cell in grid.blocked_cells() {
+            barrier_list.add(cell)?;
+        }
+        Ok(())
+    }
+}
+
+/// The path of points from `start_point` to `end_point` avoiding the barrier list's
+/// blocked cells, or `None` if there is none.
+///
+/// Points are the bottom left corners of the grid cells, as in arcade. Paths may move
+/// diagonally unless `diagonal_movement` is false, and squeeze diagonally between two
+/// blocked cells unless `cut_corners` is false. Unlike arcade, long searches are not
+/// cut short.
+#[pyfunction]
+pub fn astar_calculate_path(
+    py: Python<'_>,
+    start_point: (f32, f32),
+    end_point: (f32, f32),
+    astar_barrier_list: PyRef<'_, AStarBarrierList>,
+    diagonal_movement: Option<bool>,
+    cut_corners: Option<bool>,
+) -> PyResult<Option<Vec<(i32, i32)>>> {
+    let grid_size = astar_barrier_list.grid_size;
+    let movement = GridMovement::from_options(
+        diagonal_movement.unwrap_or(true),
+        cut_corners.unwrap_or(true),
+    );
+    let path = astar_search_native(
+        &astar_barrier_list.get_grid(py)?,
+        get_grid_cell(start_point, grid_size),
+        get_grid_cell(end_point, grid_size),
+        movement,
+    );
+    Ok(path.map(|path| {
+        path.into_iter()
+            .map(|cell| (cell.0 * grid_size, cell.1 * grid_size))
+            .collect()
+    }))
+}
+
+/// The cost of the cheapest path from every cell to the nearest goal, in row-major
+/// order, with `f32::INFINITY` for cells that can not reach one.
+///
+/// Steps cost their length times the weight of the cell stepped into, which defaults
+/// to 1. Goals outside the grid or on blocked cells are ignored.
+pub fn compute_dijkstra_map_native(
+    grid: &BarrierGrid,
+    goals: &[(i32, i32)],
+    weights: &HashMap<(i32, i32), f32>,
+    movement: GridMovement,
+) -> Vec<f32> {
+    let mut distances = vec![f32::INFINITY; grid.len()];
+    let mut open = BinaryHeap::new();
+    for &goal in goals {
+        if grid.contains(goal) && !grid.is_blocked(goal) {
+            distances[grid.index(goal)] = 0.0;
+            open.push(OpenCell {
+                estimate: 0.0,
+                cost: 0.0,
+                cell: goal,
+            });
+        }
+    }
+
+    // Paths are searched from the goals outwards, so each step is taken backwards
+    while let Some(OpenCell { cost, cell, .. }) = open.pop() {
+        if distances[grid.index(cell)] < cost {
+            continue;
+        }
+        for &offset in movement.offsets() {
+            if !grid.can_step(cell, offset, movement.cut_corners()) {
+                continue;
+            }
+            let next = (cell.0 + offset.0, cell.1 + offset.1);
+            let weight = weights.get(&cell).copied().unwrap_or(1.0);
+            let next_cost = cost + get_step_length(offset) * weight;
+            let index = grid.index(next);
+            if distances[index] <= next_cost {
+                continue;
+            }
+            distances[index] = next_cost;
+            open.push(OpenCell {
+                estimate: next_cost,
+                cost: next_cost,
+                cell: next,
+            });
+        }
+    }
+    distances
+}
+
+/// The unit direction from every cell towards its neighbour nearest to a goal, in
+/// row-major order. Goals and cells that can not reach one have a zero direction.
+/// Blocked cells also point at their nearest open neighbour, so sprites pushed into one
+/// find their way back out.
+pub fn compute_flow_directions_native(
+    grid: &BarrierGrid,
+    distances: &[f32],
+    movement: GridMovement,
+) -> Vec<(f32, f32)> {
+    (0..grid.len())
+        .map(|index| {
+            let cell = grid.cell(index);
+            let mut best = (distances[index], (0.0, 0.0));
+            for &offset in movement.offsets() {
+                if !grid.can_step(cell, offset, movement.cut_corners()) {
+                    continue;
+                }
+                let next = grid.index((cell.0 + offset.0, cell.1 + offset.1));
+                if distances[next] < best.0 {
+                    let length = get_step_length(offset);
+                    best = (
+                        distances[next],
+                        (offset.0 as f32 / length, offset.1 as f32 / length),
+                    );
+                }
+            }
+            best.1
+        })
+        .collect()
+}
+
+/// A Dijkstra map and flow field leading from every cell of a barrier list's grid to
+/// the nearest of its goals, for steering many sprites towards the same targets.
+///
+/// `goals` are world positions, or an N×2 buffer of them. `weights` maps `(column, row)`
+/// cells to how much more it costs to move into them, such as 3.0 for mud, and
+/// defaults to 1.0 everywhere. Movement is set the same way as `astar_calculate_path`.
+/// The field is a snapshot and does not change when the barrier list is recalculated.
+#[pyclass]
+pub struct FlowField {
+    #[pyo3(get)]
+    pub grid_size: i32,
+    pub grid: BarrierGrid,
+    pub distances: Vec<f32>,
+    pub directions: SharedPoints,
+}
+
+impl FlowField {
+    fn get_index(&self, position: (f32, f32)) -> Option<usize> {
+        let cell = get_grid_cell(position, self.grid_size);
+        self.grid.contains(cell).then(|| self.grid.index(cell))
+    }
+
+    pub fn get_direction_native(&self, position: (f32, f32)) -> (f32, f32) {
+        self.get_index(position)
+            .map_or((0.0, 0.0), |index| self.directions[index])
+    }
+}
+
+#[pymethods]
+impl FlowField {
+    #[new]
+    fn new(
+        py: Python<'_>,
+        astar_barrier_list: PyRef<'_, AStarBarrierList>,
+        goals: &PyAny,
+        weights: Option<&PyDict>,
+        diagonal_movement: Option<bool>,
+        cut_corners: Option<bool>,
+    ) -> PyResult<FlowField> {
+        let grid_size = astar_barrier_list.grid_size;
+        let grid = astar_barrier_list.get_grid(py)?;
+        let goals: Vec<(i32, i32)> = extract_shared_points(goals)?
+            .iter()
+            .map(|goal| get_grid_cell(*goal, grid_size))
+            .collect();
+        let mut cell_weights: HashMap<(i32, i32), f32> = HashMap::new();
+        for (cell, weight) in weights.into_iter().flatten() {
+            let weight: f32 = weight.extract()?;
+            if !(weight.is_finite() && weight > 0.0) {
+                return Err(PyValueError::new_err(format!(
+                    "cell weights must be finite and greater than zero, not {}",
+                    weight
+                )));
+            }
+            cell_weights.insert(cell.extract()?, weight);
+        }
+        let movement = GridMovement::from_options(
+            diagonal_movement.unwrap_or(true),
+            cut_corners.unwrap_or(true),
+        );
+
+        let distances = compute_dijkstra_map_native(&grid, &goals, &cell_weights, movement);
+        let directions = compute_flow_directions_native(&grid, &distances, movement).into();
+        Ok(FlowField {
+            grid_size,
+            grid,
+            distances,
+            directions,
+        })
+    }
+
+    #[getter]
+    pub fn left(&self) -> i32 {
+        self.grid.left
+    }
+
+    #[getter]
+    pub fn right(&self) -> i32 {
+        self.grid.right
+    }
+
+    #[getter]
+    pub fn bottom(&self) -> i32 {
+        self.grid.bottom
+    }
+
+    #[getter]
+    pub fn top(&self) -> i32 {
+        self.grid.top
+    }
+
+    /// Every cell's direction as a read-only float32 memoryview with one row per cell,
+    /// in row-major order starting from the bottom left cell.
+    #[getter]
+    pub fn cell_directions<'py>(&self, py: Python<'py>) -> PyResult<&'py PyAny> {
+        points_memoryview(py, self.directions.clone())
+    }
+
+    /// The cost of reaching the nearest goal from a world position, or `None` if there
+    /// is no way to one or the position is outside the grid.
+    pub fn get_distance(&self, position: (f32, f32)) -> Option<f32> {
+        self.get_index(position)
+            .map(|index| self.distances[index])
+            .filter(|distance| distance.is_finite())
+    }
+
+    /// The unit direction to move in from a world position, which is zero at a goal,
+    /// where no goal can be reached and outside the grid.
+    pub fn get_direction(&self, position: (f32, f32)) -> (f32, f32) {
+        self.get_direction_native(position)
+    }
+
+    /// The directions for many world positions, given as pairs or an N×2 buffer.
+    pub fn get_directions(&self, positions: &PyAny) -> PyResult<Vec<(f32, f32)>> {
+        Ok(extract_shared_points(positions)?
+            .iter()
+            .map(|position| self.get_direction_native(*position))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::types::PyList;
+
+    /// A 7×5 grid with a wall down column 3 that has a gap at the top.
+    fn walled_grid() -> BarrierGrid {
+        let mut grid = BarrierGrid::new(0, 6, 0, 4);
+        for y in 0..4 {
+            grid.set_blocked((3, y), true);
+        }
+        grid
+    }
+
+    fn get_path_cost(path: &[(i32, i32)]) -> f32 {
+        path.windows(2)
+            .map(|step| {
+                if step[0].0 == step[1].0 || step[0].1 == step[1].1 {
+                    1.0
+                } else {
+                    SQRT_2
+                }
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_barrier_grid() {
+        let mut grid = BarrierGrid::new(-2, 2, -1, 1);
+        grid.set_blocked((-2, -1), true);
+        grid.set_blocked((2, 1), true);
+        grid.set_blocked((5, 5), true);
+        assert!(grid.is_blocked((-2, -1)));
+        assert!(!grid.is_blocked((0, 0)));
+        assert!(!grid.is_blocked((5, 5)));
+        assert_eq!(
+            grid.blocked_cells().collect::<Vec<_>>(),
+            vec![(-2, -1), (2, 1)]
+        );
+        grid.clear();
+        assert_eq!(grid.blocked_cells().count(), 0);
+    }
+
+    #[test]
+    fn test_astar_around_wall() {
+        let grid = walled_grid();
+        let path = astar_search_native(&grid, (0, 0), (6, 0), GridMovement::Diagonal).unwrap();
+        assert_eq!(path.first(), Some(&(0, 0)));
+        assert_eq!(path.last(), Some(&(6, 0)));
+        assert!(path.iter().all(|cell| !grid.is_blocked(*cell)));
+        assert!(path.contains(&(3, 4)));
+        assert!((get_path_cost(&path) - (2.0 + 6.0 * SQRT_2)).abs() < 1.0e-4);
+
+        let path = astar_search_native(&grid, (0, 0), (6, 0), GridMovement::Orthogonal).unwrap();
+        assert_eq!(get_path_cost(&path), 14.0);
+    }
+
+    #[test]
+    fn test_barrier_list_edits() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let globals = PyDict::new(py);
+            globals
+                .set_item("HitBox", py.get_type::<crate::hitbox::HitBox>())
+                .unwrap();
+            py.run(
+                r#"
+class Sprite:
+    position = (0, 0)
+    _hit_box = HitBox([(1, 1), (2, 1), (2, 2)])
+"#,
+                Some(globals),
+                None,
+            )
+            .unwrap();
+            let sprite = py.eval("Sprite()", Some(globals), None).unwrap();
+            let barrier_list = PyCell::new(
+                py,
+                AStarBarrierList::new(
+                    py,
+                    sprite.into(),
+                    PyList::empty(py).into(),
+                    10,
+                    0.0,
+                    60.0,
+                    0.0,
+                    40.0,
+                )
+                .unwrap(),
+            )
+            .unwrap();
+            let cells = barrier_list.getattr("barrier_list").unwrap();
+            assert!(cells.is(barrier_list.getattr("barrier_list").unwrap()));
+            for y in 0..4 {
+                cells.call_method1("add", ((3, y),)).unwrap();
+            }
+            assert!(barrier_list.borrow().is_blocked(py, (3, 0)).unwrap());
+
+            let path = astar_calculate_path(
+                py,
+                (0.0, 0.0),
+                (60.0, 0.0),
+                barrier_list.borrow(),
+                None,
+                None,
+            )
+            .unwrap()
+            .unwrap();
+            assert!(path.contains(&(30, 40)));
+
+            barrier_list
+                .setattr("barrier_list", PySet::new(py, &[(3, 4)]).unwrap())
+                .unwrap();
+            let path = astar_calculate_path(
+                py,
+                (0.0, 0.0),
+                (60.0, 0.0),
+                barrier_list.borrow(),
+                None,
+                None,
+            )
+            .unwrap()
+            .unwrap();
+            assert_eq!(path.len(), 7);
+
+            barrier_list.borrow().recalculate(py).unwrap();
+            assert!(barrier_list
+                .getattr("barrier_list")
+                .unwrap()
+                .downcast::<PySet>()
+                .unwrap()
+                .is_empty());
+        });
+    }
+
+    #[test]
+    fn test_astar_corner_cutting() {
+        // Two blocked cells touching at a corner
+        let mut grid = BarrierGrid::new(0, 1, 0, 1);
+        grid.set_blocked((1, 0), true);
+        grid.set_blocked((0, 1), true);
+        let path = astar_search_native(&grid, (0, 0), (1, 1), GridMovement::Diagonal);
+        assert_eq!(path, Some(vec![(0, 0), (1, 1)]));
+        let path =
+            astar_search_native(&grid, (0, 0), (1, 1), GridMovement::DiagonalNoCornerCutting);
+        assert_eq!(path, None);
+    }
+
+    #[test]
+    fn test_dijkstra_map() {
+        let grid = walled_grid();
+        let distances =
+            compute_dijkstra_map_native(&grid, &[(6, 0)], &HashMap::new(), GridMovement::Diagonal);
+        assert_eq!(distances[grid.index((6, 0))], 0.0);
+        assert_eq!(distances[grid.index((5, 0))], 1.0);
+        assert_eq!(distances[grid.index((3, 0))], f32::INFINITY);
+        assert!((distances[grid.index((0, 0))] - (2.0 + 6.0 * SQRT_2)).abs() < 1.0e-4);
+
+        // The nearest of several goals wins, and blocked goals are ignored
+        let goals = [(6, 0), (0, 1), (3, 2)];
+        let distances =
+            compute_dijkstra_map_native(&grid, &goals, &HashMap::new(), GridMovement::Orthogonal);
+        assert_eq!(distances[grid.index((0, 0))], 1.0);
+        assert_eq!(distances[grid.index((2, 2))], 3.0);
+    }
+
+    #[test]
+    fn test_dijkstra_map_weights() {
+        let grid = BarrierGrid::new(0, 2, 0, 1);
+        // Moving into the middle of the bottom row is expensive, so go around it
+        let weights = HashMap::from([((1, 0), 5.0)]);
+        let distances =
+            compute_dijkstra_map_native(&grid, &[(2, 0)], &weights, GridMovement::Orthogonal);
+        assert_eq!(distances[grid.index((1, 0))], 1.0);
+        assert_eq!(distances[grid.index((0, 0))], 4.0);
+    }
+
+    #[test]
+    fn test_flow_directions() {
+        let grid = walled_grid();
+        let movement = GridMovement::Diagonal;
+        let distances = compute_dijkstra_map_native(&grid, &[(6, 0)], &HashMap::new(), movement);
+        let directions = compute_flow_directions_native(&grid, &distances, movement);
+        assert_eq!(directions[grid.index((6, 0))], (0.0, 0.0));
+        assert_eq!(directions[grid.index((5, 0))], (1.0, 0.0));
+        assert_eq!(directions[grid.index((3, 0))], (1.0, 0.0));
+        // Left of the wall everything heads up and over it
+        let (x, y) = directions[grid.index((0, 0))];
+        assert!(x > 0.0 && y > 0.0 && (x * x + y * y - 1.0).abs() < 1.0e-6);
+
+        // Following the directions reaches the goal
+        let mut cell = (0, 0);
+        for _ in 0..20 {
+            let (x, y) = directions[grid.index(cell)];
+            cell = (
+                cell.0 + x.signum() as i32 * (x != 0.0) as i32,
+                cell.1 + y.signum() as i32 * (y != 0.0) as i32,
+            );
+        }
+        assert_eq!(cell, (6, 0));
+    }
+
+    #[test]
+    fn test_astar_unreachable() {
+        let mut grid = walled_grid();
+        grid.set_blocked((3, 4), true);
+        assert_eq!(
+            astar_search_native(&grid, (0, 0), (6, 0), GridMovement::Diagonal),
+            None
+        );
+        // Blocked or outside ends can not be reached, but a path to the start is trivial
+        assert_eq!(
+            astar_search_native(&grid, (0, 0), (3, 0), GridMovement::Diagonal),
+            None
+        );
+        assert_eq!(
+            astar_search_native(&grid, (0, 0), (9, 0), GridMovement::Diagonal),
+            None
+        );
+        assert_eq!(
+            astar_search_native(&grid, (2, 2), (2, 2), GridMovement::Diagonal),
+            Some(vec![(2, 2)])
+        );
+    }
+}
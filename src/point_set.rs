@@ -0,0 +1,364 @@
+use std::ffi::{c_char, c_int, c_void};
+use std::ptr;
+use std::sync::Arc;
+
+use pyo3::buffer::{Element, PyBuffer};
+use pyo3::exceptions::{PyBufferError, PyIndexError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use pyo3::{ffi, AsPyPointer};
+
+/// Hit box points that any number of hit boxes can hold without copying.
+pub type SharedPoints = Arc<[(f32, f32)]>;
+
+/// Adjusted hit box points that stay alive while a buffer exported from them is in use.
+/// A hit box refills them in place unless a buffer still holds them.
+pub type SharedAdjustedPoints = Arc<Vec<(f32, f32)>>;
+
+// Buffers hand out points as rows of two packed floats.
+const _: () =
+    assert!(std::mem::size_of::<(f32, f32)>() == 8 && std::mem::offset_of!((f32, f32), 1) == 4);
+
+/// Replace the contents of `cache` with `points`, reusing its allocation unless an
+/// exported buffer still shares it.
+pub fn refill_shared_points(
+    cache: &mut SharedAdjustedPoints,
+    points: impl IntoIterator<Item = (f32, f32)>,
+) {
+    match Arc::get_mut(cache) {
+        Some(cached) => {
+            cached.clear();
+            cached.extend(points);
+        }
+        None => *cache = Arc::new(points.into_iter().collect()),
+    }
+}
+
+/// An immutable, reference counted list of hit box points.
+///
+/// Hit boxes created from a point set share its data instead of each owning a copy,
+/// which adds up with thousands of sprites using the same texture.
+#[derive(Clone)]
+#[pyclass(frozen)]
+pub struct PointSet {
+    pub points: SharedPoints,
+}
+
+impl From<SharedPoints> for PointSet {
+    fn from(points: SharedPoints) -> PointSet {
+        PointSet { points }
+    }
+}
+
+#[pymethods]
+impl PointSet {
+    #[new]
+    fn new(points: Vec<(f32, f32)>) -> PointSet {
+        PointSet {
+            points: points.into(),
+        }
+    }
+
+    #[getter]
+    pub fn points(&self) -> Vec<(f32, f32)> {
+        self.points.to_vec()
+    }
+
+    pub fn __len__(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn __getitem__(&self, index: isize) -> PyResult<(f32, f32)> {
+        let length = self.points.len() as isize;
+        let position = if index < 0 { index + length } else { index };
+        if position < 0 || position >= length {
+            return Err(PyIndexError::new_err("point set index out of range"));
+        }
+        Ok(self.points[position as usize])
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("PointSet({:?})", self.points)
+    }
+}
+
+/// Take hit box points from a `PointSet`, sharing its data, from a float32 or float64
+/// buffer such as an N×2 numpy array, or from any sequence of points, copying them once.
+pub fn extract_shared_points(points: &PyAny) -> PyResult<SharedPoints> {
+    if let Ok(point_set) = points.downcast::<PyCell<PointSet>>() {
+        return Ok(point_set.get().points.clone());
+    }
+    if let Ok(buffer) = PyBuffer::<f32>::get(points) {
+        return extract_buffer_points(points.py(), &buffer, |value| value);
+    }
+    if let Ok(buffer) = PyBuffer::<f64>::get(points) {
+        return extract_buffer_points(points.py(), &buffer, |value| value as f32);
+    }
+    let points: Vec<(f32, f32)> = points.extract()?;
+    Ok(points.into())
+}
+
+/// Take numbers from a float32 or float64 buffer, such as a numpy array, or from any
+/// sequence of numbers.
+pub fn extract_values(values: &PyAny) -> PyResult<Vec<f32>> {
+    if let Ok(buffer) = PyBuffer::<f32>::get(values) {
+        return buffer.to_vec(values.py());
+    }
+    if let Ok(buffer) = PyBuffer::<f64>::get(values) {
+        let values = buffer.to_vec(values.py())?;
+        return Ok(values.into_iter().map(|value| value as f32).collect());
+    }
+    values.extract()
+}
+
+/// Read points from an N×2 buffer, or a flat one holding x and y in turn.
+fn extract_buffer_points<T: Element + Copy>(
+    py: Python<'_>,
+    buffer: &PyBuffer<T>,
+    convert: impl Fn(T) -> f32,
+) -> PyResult<SharedPoints> {
+    let is_flat = buffer.dimensions() == 1 && buffer.item_count().is_multiple_of(2);
+    if !is_flat && buffer.shape() != [buffer.item_count() / 2, 2] {
+        return Err(PyValueError::new_err(format!(
+            "point buffers must have the shape (N, 2), not {:?}",
+            buffer.shape()
+        )));
+    }
+    let values = buffer.to_vec(py)?;
+    Ok(values
+        .chunks_exact(2)
+        .map(|point| (convert(point[0]), convert(point[1])))
+        .collect())
+}
+
+/// Points owned by a `PointBuffer`, either the raw points or a hit box's adjusted ones.
+enum BufferPoints {
+    Points(SharedPoints),
+    Adjusted(SharedAdjustedPoints),
+}
+
+/// A read-only float32 N×2 view of hit box points for the Python buffer protocol.
+///
+/// The buffer holds a reference to the points, so it stays valid after the hit box
+/// moves or changes its points; it shows them as they were when it was created.
+/// Consumers that do not ask for a shape get the points as plain bytes.
+#[pyclass(frozen)]
+pub struct PointBuffer {
+    points: BufferPoints,
+    shape: [ffi::Py_ssize_t; 2],
+    strides: [ffi::Py_ssize_t; 2],
+}
+
+impl PointBuffer {
+    fn new(points: BufferPoints) -> PointBuffer {
+        let length = match &points {
+            BufferPoints::Points(points) => points.len(),
+            BufferPoints::Adjusted(points) => points.len(),
+        };
+        PointBuffer {
+            points,
+            shape: [length as ffi::Py_ssize_t, 2],
+            strides: [8, 4],
+        }
+    }
+
+    fn as_slice(&self) -> &[(f32, f32)] {
+        match &self.points {
+            BufferPoints::Points(points) => points,
+            BufferPoints::Adjusted(points) => points,
+        }
+    }
+}
+
+#[pymethods]
+impl PointBuffer {
+    unsafe fn __getbuffer__(
+        slf: PyRef<'_, Self>,
+        view: *mut ffi::Py_buffer,
+        flags: c_int,
+    ) -> PyResult<()> {
+        if view.is_null() {
+            return Err(PyBufferError::new_err("view is null"));
+        }
+        if flags & ffi::PyBUF_WRITABLE == ffi::PyBUF_WRITABLE {
+            return Err(PyBufferError::new_err("point buffers are read-only"));
+        }
+        let has_format = flags & ffi::PyBUF_FORMAT == ffi::PyBUF_FORMAT;
+        let has_shape = flags & ffi::PyBUF_ND == ffi::PyBUF_ND;
+        if has_shape && !has_format {
+            return Err(PyBufferError::new_err(
+                "point buffers can only be shaped as float32",
+            ));
+        }
+        let points = slf.as_slice();
+
+        (*view).obj = slf.as_ptr();
+        ffi::Py_INCREF((*view).obj);
+        (*view).buf = points.as_ptr() as *mut c_void;
+        (*view).len = std::mem::size_of_val(points) as ffi::Py_ssize_t;
+        (*view).readonly = 1;
+        if has_shape {
+            (*view).itemsize = std::mem::size_of::<f32>() as ffi::Py_ssize_t;
+            (*view).format = c"f".as_ptr() as *mut c_char;
+            (*view).ndim = 2;
+            (*view).shape = slf.shape.as_ptr() as *mut ffi::Py_ssize_t;
+        } else {
+            // Plain bytes for simple requests, as `PyBuffer_FillInfo` gives them
+            (*view).itemsize = 1;
+            (*view).format = if has_format {
+                c"B".as_ptr() as *mut c_char
+            } else {
+                ptr::null_mut()
+            };
+            (*view).ndim = 1;
+            (*view).shape = ptr::null_mut();
+        }
+        (*view).strides = if flags & ffi::PyBUF_STRIDES == ffi::PyBUF_STRIDES {
+            slf.strides.as_ptr() as *mut ffi::Py_ssize_t
+        } else {
+            ptr::null_mut()
+        };
+        (*view).suboffsets = ptr::null_mut();
+        (*view).internal = ptr::null_mut();
+        Ok(())
+    }
+
+    unsafe fn __releasebuffer__(&self, _view: *mut ffi::Py_buffer) {}
+
+    pub fn __len__(&self) -> usize {
+        self.as_slice().len()
+    }
+}
+
+fn memoryview(py: Python<'_>, points: BufferPoints) -> PyResult<&PyAny> {
+    let buffer = PyCell::new(py, PointBuffer::new(points))?;
+    unsafe { py.from_owned_ptr_or_err(ffi::PyMemoryView_FromObject(buffer.as_ptr())) }
+}
+
+/// A memoryview of hit box points that shares them instead of copying.
+pub fn points_memoryview(py: Python<'_>, points: SharedPoints) -> PyResult<&PyAny> {
+    memoryview(py, BufferPoints::Points(points))
+}
+
+/// A memoryview of adjusted hit box points that shares them instead of copying.
+pub fn adjusted_points_memoryview(
+    py: Python<'_>,
+    points: SharedAdjustedPoints,
+) -> PyResult<&PyAny> {
+    memoryview(py, BufferPoints::Adjusted(points))
+}
+
+/// Point sets keyed by texture, or anything else hashable, so every hit box made for
+/// the same key shares one copy of the points.
+///
+/// Entries are kept until removed, so textures that are unloaded should be removed
+/// or the cache cleared.
+#[pyclass]
+pub struct PointSetCache {
+    point_sets: Py<PyDict>,
+}
+
+#[pymethods]
+impl PointSetCache {
+    #[new]
+    fn new(py: Python<'_>) -> PointSetCache {
+        PointSetCache {
+            point_sets: PyDict::new(py).into(),
+        }
+    }
+
+    /// The point set stored for `key`, made from `points` the first time the key is used.
+    pub fn get(&self, py: Python<'_>, key: &PyAny, points: &PyAny) -> PyResult<Py<PointSet>> {
+        let point_sets = self.point_sets.as_ref(py);
+        if let Some(point_set) = point_sets.get_item(key)? {
+            return point_set.extract();
+        }
+        let point_set = Py::new(py, PointSet::from(extract_shared_points(points)?))?;
+        point_sets.set_item(key, point_set.clone_ref(py))?;
+        Ok(point_set)
+    }
+
+    pub fn remove(&self, py: Python<'_>, key: &PyAny) -> PyResult<()> {
+        self.point_sets.as_ref(py).del_item(key)
+    }
+
+    pub fn clear(&self, py: Python<'_>) {
+        self.point_sets.as_ref(py).clear();
+    }
+
+    pub fn __len__(&self, py: Python<'_>) -> usize {
+        self.point_sets.as_ref(py).len()
+    }
+
+    pub fn __contains__(&self, py: Python<'_>, key: &PyAny) -> PyResult<bool> {
+        self.point_sets.as_ref(py).contains(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_point_set_indexing() {
+        let point_set = PointSet::new(vec![(0.0, 1.0), (2.0, 3.0)]);
+        assert_eq!(point_set.__len__(), 2);
+        assert_eq!(point_set.__getitem__(1).unwrap(), (2.0, 3.0));
+        assert_eq!(point_set.__getitem__(-2).unwrap(), (0.0, 1.0));
+        assert!(point_set.__getitem__(2).is_err());
+        assert!(point_set.__getitem__(-3).is_err());
+    }
+
+    #[test]
+    fn test_point_buffer_requests() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let points: SharedPoints = vec![(1.0, 2.0), (3.0, 4.0), (5.0, 6.0)].into();
+            let buffer = PyCell::new(py, PointBuffer::new(BufferPoints::Points(points))).unwrap();
+            let get_view = |flags: c_int| unsafe {
+                let mut view = std::mem::MaybeUninit::<ffi::Py_buffer>::uninit();
+                if ffi::PyObject_GetBuffer(buffer.as_ptr(), view.as_mut_ptr(), flags) != 0 {
+                    return Err(PyErr::fetch(py));
+                }
+                let view = view.assume_init();
+                let fields = (view.len, view.itemsize, view.ndim, view.shape.is_null());
+                ffi::PyBuffer_Release(&mut { view });
+                Ok(fields)
+            };
+
+            // Simple consumers see the points as 24 bytes
+            assert_eq!(get_view(ffi::PyBUF_SIMPLE).unwrap(), (24, 1, 1, true));
+            assert_eq!(get_view(ffi::PyBUF_FORMAT).unwrap(), (24, 1, 1, true));
+            assert_eq!(get_view(ffi::PyBUF_FULL_RO).unwrap(), (24, 4, 2, false));
+            assert!(get_view(ffi::PyBUF_ND)
+                .unwrap_err()
+                .is_instance_of::<PyBufferError>(py));
+            assert!(get_view(ffi::PyBUF_FULL)
+                .unwrap_err()
+                .is_instance_of::<PyBufferError>(py));
+
+            let view = py
+                .import("builtins")
+                .unwrap()
+                .getattr("memoryview")
+                .unwrap()
+                .call1((buffer,))
+                .unwrap();
+            let rows: Vec<Vec<f32>> = view.call_method0("tolist").unwrap().extract().unwrap();
+            assert_eq!(rows, vec![vec![1.0, 2.0], vec![3.0, 4.0], vec![5.0, 6.0]]);
+        });
+    }
+
+    #[test]
+    fn test_refill_shared_points() {
+        let mut cache: SharedAdjustedPoints = Arc::new(vec![(0.0, 0.0)]);
+        let allocation = cache.as_ptr();
+        refill_shared_points(&mut cache, [(1.0, 2.0)]);
+        assert_eq!(cache.as_ptr(), allocation);
+
+        let exported = cache.clone();
+        refill_shared_points(&mut cache, [(3.0, 4.0), (5.0, 6.0)]);
+        assert_eq!(*exported, vec![(1.0, 2.0)]);
+        assert_eq!(*cache, vec![(3.0, 4.0), (5.0, 6.0)]);
+    }
+}
@@ -0,0 +1,190 @@
+use crate::geometry::{
+    are_capsules_intersecting, are_circles_intersecting, are_polygons_intersecting_native,
+    cast_ray_against_capsule_native, cast_ray_against_circle_native,
+    cast_ray_against_polygon_native, is_capsule_intersecting_polygon_native,
+    is_circle_intersecting_polygon_native, RayHit,
+};
+
+/// World space collision geometry of a hit box.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Shape<'a> {
+    Polygon(&'a [(f32, f32)]),
+    Circle {
+        center: (f32, f32),
+        radius: f32,
+    },
+    Capsule {
+        start: (f32, f32),
+        end: (f32, f32),
+        radius: f32,
+    },
+    /// Convex polygons which collide if any one of them does
+    Compound(&'a [Vec<(f32, f32)>]),
+}
+
+impl Shape<'_> {
+    pub fn intersects(&self, other: &Shape) -> bool {
+        match (*self, *other) {
+            (Shape::Compound(parts), other) | (other, Shape::Compound(parts)) => parts
+                .iter()
+                .any(|part| Shape::Polygon(part).intersects(&other)),
+            (Shape::Polygon(poly_a), Shape::Polygon(poly_b)) => {
+                are_polygons_intersecting_native(poly_a, poly_b)
+            }
+            (Shape::Circle { center, radius }, Shape::Polygon(polygon))
+            | (Shape::Polygon(polygon), Shape::Circle { center, radius }) => {
+                is_circle_intersecting_polygon_native(center, radius, polygon)
+            }
+            (
+                Shape::Circle {
+                    center: center_a,
+                    radius: radius_a,
+                },
+                Shape::Circle {
+                    center: center_b,
+                    radius: radius_b,
+                },
+            ) => are_circles_intersecting(center_a, radius_a, center_b, radius_b),
+            (Shape::Capsule { start, end, radius }, Shape::Polygon(polygon))
+            | (Shape::Polygon(polygon), Shape::Capsule { start, end, radius }) => {
+                is_capsule_intersecting_polygon_native(start, end, radius, polygon)
+            }
+            (
+                Shape::Capsule { start, end, radius },
+                Shape::Circle {
+                    center,
+                    radius: circle_radius,
+                },
+            )
+            | (
+                Shape::Circle {
+                    center,
+                    radius: circle_radius,
+                },
+                Shape::Capsule { start, end, radius },
+            ) => are_capsules_intersecting(start, end, radius, center, center, circle_radius),
+            (
+                Shape::Capsule {
+                    start: start_a,
+                    end: end_a,
+                    radius: radius_a,
+                },
+                Shape::Capsule {
+                    start: start_b,
+                    end: end_b,
+                    radius: radius_b,
+                },
+            ) => are_capsules_intersecting(start_a, end_a, radius_a, start_b, end_b, radius_b),
+        }
+    }
+
+    /// Where a ray with unit `direction` first hits the shape within `max_distance`.
+    pub fn cast_ray(
+        &self,
+        origin: (f32, f32),
+        direction: (f32, f32),
+        max_distance: f32,
+    ) -> Option<RayHit> {
+        match *self {
+            Shape::Polygon(polygon) => {
+                cast_ray_against_polygon_native(origin, direction, polygon, max_distance)
+            }
+            Shape::Circle { center, radius } => {
+                cast_ray_against_circle_native(origin, direction, center, radius, max_distance)
+            }
+            Shape::Capsule { start, end, radius } => {
+                cast_ray_against_capsule_native(origin, direction, start, end, radius, max_distance)
+            }
+            Shape::Compound(parts) => parts.iter().fold(None, |nearest, part| {
+                RayHit::nearest(
+                    nearest,
+                    cast_ray_against_polygon_native(origin, direction, part, max_distance),
+                )
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shape_intersects() {
+        let square = [(0.0, 0.0), (0.0, 10.0), (10.0, 10.0), (10.0, 0.0)];
+        let polygon = Shape::Polygon(&square);
+        let near = Shape::Circle {
+            center: (12.0, 5.0),
+            radius: 2.5,
+        };
+        let far = Shape::Circle {
+            center: (30.0, 5.0),
+            radius: 2.5,
+        };
+        assert!(polygon.intersects(&polygon));
+        assert!(polygon.intersects(&near));
+        assert!(near.intersects(&polygon));
+        assert!(!far.intersects(&polygon));
+        assert!(!far.intersects(&near));
+        assert!(far.intersects(&far));
+    }
+
+    #[test]
+    fn test_capsule_intersects() {
+        let square = [(0.0, 0.0), (0.0, 10.0), (10.0, 10.0), (10.0, 0.0)];
+        let capsule = Shape::Capsule {
+            start: (12.0, -10.0),
+            end: (12.0, 30.0),
+            radius: 2.5,
+        };
+        let circle = Shape::Circle {
+            center: (16.0, 30.0),
+            radius: 2.0,
+        };
+        let other = Shape::Capsule {
+            start: (20.0, 0.0),
+            end: (30.0, 0.0),
+            radius: 1.0,
+        };
+        assert!(capsule.intersects(&Shape::Polygon(&square)));
+        assert!(Shape::Polygon(&square).intersects(&capsule));
+        assert!(capsule.intersects(&circle));
+        assert!(circle.intersects(&capsule));
+        assert!(!capsule.intersects(&other));
+        assert!(other.intersects(&other));
+    }
+
+    #[test]
+    fn test_compound_intersects() {
+        let parts = vec![
+            vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)],
+            vec![(5.0, 0.0), (6.0, 0.0), (6.0, 1.0), (5.0, 1.0)],
+        ];
+        let compound = Shape::Compound(&parts);
+        let between = Shape::Circle {
+            center: (3.0, 0.5),
+            radius: 1.0,
+        };
+        let on_second = Shape::Circle {
+            center: (6.5, 0.5),
+            radius: 1.0,
+        };
+        assert!(!compound.intersects(&between));
+        assert!(compound.intersects(&on_second));
+        assert!(on_second.intersects(&compound));
+        assert!(compound.intersects(&compound));
+        assert!(!Shape::Compound(&[]).intersects(&compound));
+    }
+
+    #[test]
+    fn test_compound_cast_ray() {
+        let parts = vec![
+            vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)],
+            vec![(5.0, 0.0), (6.0, 0.0), (6.0, 1.0), (5.0, 1.0)],
+        ];
+        let compound = Shape::Compound(&parts);
+        let hit = compound.cast_ray((10.0, 0.5), (-1.0, 0.0), 100.0).unwrap();
+        assert_eq!(hit.point, (6.0, 0.5));
+        assert!(compound.cast_ray((3.0, 5.0), (0.0, -1.0), 100.0).is_none());
+    }
+}
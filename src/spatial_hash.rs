@@ -0,0 +1,407 @@
+use std::collections::HashMap;
+
+use pyo3::exceptions::{PyKeyError, PyValueError};
+use pyo3::intern;
+use pyo3::prelude::*;
+use pyo3::types::PySet;
+
+use crate::geometry::Aabb;
+
+type CellRange = ((i32, i32), (i32, i32));
+
+/// Uniform grid of buckets, each holding the keys of the items overlapping that cell.
+pub struct SpatialGrid {
+    cell_size: f32,
+    contents: HashMap<(i32, i32), Vec<usize>>,
+    cells_for_key: HashMap<usize, CellRange>,
+}
+
+impl SpatialGrid {
+    pub fn new(cell_size: f32) -> SpatialGrid {
+        SpatialGrid {
+            cell_size,
+            contents: HashMap::new(),
+            cells_for_key: HashMap::new(),
+        }
+    }
+
+    pub fn hash(&self, point: (f32, f32)) -> (i32, i32) {
+        (
+            (point.0 / self.cell_size).floor() as i32,
+            (point.1 / self.cell_size).floor() as i32,
+        )
+    }
+
+    fn cell_range(&self, bounds: &Aabb) -> CellRange {
+        (
+            self.hash((bounds.left, bounds.bottom)),
+            self.hash((bounds.right, bounds.top)),
+        )
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells_for_key.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells_for_key.is_empty()
+    }
+
+    pub fn contains(&self, key: usize) -> bool {
+        self.cells_for_key.contains_key(&key)
+    }
+
+    pub fn clear(&mut self) {
+        self.contents.clear();
+        self.cells_for_key.clear();
+    }
+
+    /// Add an item to every bucket its bounds overlap, replacing any previous entry for the key.
+    pub fn insert(&mut self, key: usize, bounds: &Aabb) {
+        self.remove(key);
+        let range = self.cell_range(bounds);
+        for i in range.0 .0..=range.1 .0 {
+            for j in range.0 .1..=range.1 .1 {
+                self.contents.entry((i, j)).or_default().push(key);
+            }
+        }
+        self.cells_for_key.insert(key, range);
+    }
+
+    /// Remove an item from all of its buckets. Returns false if the key was not present.
+    pub fn remove(&mut self, key: usize) -> bool {
+        let Some(range) = self.cells_for_key.remove(&key) else {
+            return false;
+        };
+        for i in range.0 .0..=range.1 .0 {
+            for j in range.0 .1..=range.1 .1 {
+                if let Some(bucket) = self.contents.get_mut(&(i, j)) {
+                    if let Some(index) = bucket.iter().position(|k| *k == key) {
+                        bucket.swap_remove(index);
+                    }
+                    if bucket.is_empty() {
+                        self.contents.remove(&(i, j));
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    /// Move an item to new bounds, only touching the buckets if its cell range changed.
+    /// Returns false if the key was not present.
+    pub fn update(&mut self, key: usize, bounds: &Aabb) -> bool {
+        match self.cells_for_key.get(&key) {
+            None => false,
+            Some(range) if *range == self.cell_range(bounds) => true,
+            Some(_) => {
+                self.insert(key, bounds);
+                true
+            }
+        }
+    }
+
+    /// Every key sharing at least one bucket with the given bounds, without duplicates.
+    pub fn query(&self, bounds: &Aabb) -> Vec<usize> {
+        let mut found: Vec<usize> = Vec::new();
+        if bounds.is_empty() {
+            return found;
+        }
+        let range = self.cell_range(bounds);
+        for i in range.0 .0..=range.1 .0 {
+            for j in range.0 .1..=range.1 .1 {
+                if let Some(bucket) = self.contents.get(&(i, j)) {
+                    found.extend_from_slice(bucket);
+                }
+            }
+        }
+        if range.0 != range.1 {
+            found.sort_unstable();
+            found.dedup();
+        }
+        found
+    }
+
+    /// Every key sharing a bucket with the segment from `start` to `end`, without
+    /// duplicates.
+    ///
+    /// Only the cells the segment passes through are visited. A segment crossing more
+    /// cells than there are buckets returns every key instead, so long rays cost no
+    /// more than going through the whole grid.
+    pub fn query_segment(&self, start: (f32, f32), end: (f32, f32)) -> Vec<usize> {
+        let first = self.hash(start);
+        let last = self.hash(end);
+        let cell_count =
+            (last.0 as i64 - first.0 as i64).abs() + (last.1 as i64 - first.1 as i64).abs() + 1;
+        let mut found: Vec<usize> = if cell_count > self.contents.len() as i64 {
+            self.cells_for_key.keys().copied().collect()
+        } else {
+            // Amanatides-Woo: step into whichever neighbouring cell the segment reaches
+            // first, tracking how far along it the next border on each axis is
+            let delta = (end.0 - start.0, end.1 - start.1);
+            let border_crossing = |cell: i32, step: i32, origin: f32, delta: f32| {
+                if step == 0 {
+                    return (f32::INFINITY, f32::INFINITY);
+                }
+                let border = (cell + step.max(0)) as f32 * self.cell_size;
+                ((border - origin) / delta, self.cell_size / delta.abs())
+            };
+            let step = ((last.0 - first.0).signum(), (last.1 - first.1).signum());
+            let (mut next_x, step_x) = border_crossing(first.0, step.0, start.0, delta.0);
+            let (mut next_y, step_y) = border_crossing(first.1, step.1, start.1, delta.1);
+
+            let mut found = Vec::new();
+            let mut cell = first;
+            loop {
+                if let Some(bucket) = self.contents.get(&cell) {
+                    found.extend_from_slice(bucket);
+                }
+                if cell == last {
+                    break;
+                }
+                // Never step past the last cell on an axis, whatever rounding says
+                if cell.1 == last.1 || (cell.0 != last.0 && next_x < next_y) {
+                    cell.0 += step.0;
+                    next_x += step_x;
+                } else {
+                    cell.1 += step.1;
+                    next_y += step_y;
+                }
+            }
+            found
+        };
+        found.sort_unstable();
+        found.dedup();
+        found
+    }
+}
+
+fn get_sprite_bounds(py: Python<'_>, sprite: &PyAny) -> PyResult<Aabb> {
+    Ok(Aabb {
+        left: sprite.getattr(intern!(py, "left"))?.extract()?,
+        right: sprite.getattr(intern!(py, "right"))?.extract()?,
+        bottom: sprite.getattr(intern!(py, "bottom"))?.extract()?,
+        top: sprite.getattr(intern!(py, "top"))?.extract()?,
+    })
+}
+
+fn sprite_key(sprite: &PyAny) -> usize {
+    sprite.as_ptr() as usize
+}
+
+#[pyclass(module = "arcade.sprite_list.spatial_hash")]
+pub struct SpatialHash {
+    grid: SpatialGrid,
+    sprites: HashMap<usize, PyObject>,
+    #[pyo3(get)]
+    pub cell_size: i32,
+}
+
+impl SpatialHash {
+    /// Sprites whose buckets overlap the given bounds.
+    pub fn query_native(&self, py: Python<'_>, bounds: &Aabb) -> Vec<PyObject> {
+        self.grid
+            .query(bounds)
+            .iter()
+            .map(|key| self.sprites[key].clone_ref(py))
+            .collect()
+    }
+
+    /// Sprites whose buckets the segment from `start` to `end` passes through.
+    pub fn query_segment_native(
+        &self,
+        py: Python<'_>,
+        start: (f32, f32),
+        end: (f32, f32),
+    ) -> Vec<PyObject> {
+        self.grid
+            .query_segment(start, end)
+            .iter()
+            .map(|key| self.sprites[key].clone_ref(py))
+            .collect()
+    }
+
+    fn query_set<'py>(&self, py: Python<'py>, bounds: &Aabb) -> PyResult<&'py PySet> {
+        PySet::new(py, &self.query_native(py, bounds))
+    }
+}
+
+#[pymethods]
+impl SpatialHash {
+    #[new]
+    fn new(cell_size: i32) -> PyResult<SpatialHash> {
+        if cell_size <= 0 {
+            return Err(PyValueError::new_err("cell_size must be greater than 0"));
+        }
+        Ok(SpatialHash {
+            grid: SpatialGrid::new(cell_size as f32),
+            sprites: HashMap::new(),
+            cell_size,
+        })
+    }
+
+    pub fn hash(&self, point: (f32, f32)) -> (i32, i32) {
+        self.grid.hash(point)
+    }
+
+    pub fn reset(&mut self) {
+        self.grid.clear();
+        self.sprites.clear();
+    }
+
+    pub fn add(&mut self, py: Python<'_>, sprite: &PyAny) -> PyResult<()> {
+        let bounds = get_sprite_bounds(py, sprite)?;
+        let key = sprite_key(sprite);
+        self.grid.insert(key, &bounds);
+        self.sprites.insert(key, sprite.into());
+        Ok(())
+    }
+
+    pub fn r#move(&mut self, py: Python<'_>, sprite: &PyAny) -> PyResult<()> {
+        let bounds = get_sprite_bounds(py, sprite)?;
+        if !self.grid.update(sprite_key(sprite), &bounds) {
+            return Err(PyKeyError::new_err(sprite.to_object(py)));
+        }
+        Ok(())
+    }
+
+    pub fn remove(&mut self, py: Python<'_>, sprite: &PyAny) -> PyResult<()> {
+        let key = sprite_key(sprite);
+        if !self.grid.remove(key) {
+            return Err(PyKeyError::new_err(sprite.to_object(py)));
+        }
+        self.sprites.remove(&key);
+        Ok(())
+    }
+
+    pub fn get_sprites_near_sprite<'py>(
+        &self,
+        py: Python<'py>,
+        sprite: &PyAny,
+    ) -> PyResult<&'py PySet> {
+        let bounds = get_sprite_bounds(py, sprite)?;
+        self.query_set(py, &bounds)
+    }
+
+    pub fn get_sprites_near_point<'py>(
+        &self,
+        py: Python<'py>,
+        point: (f32, f32),
+    ) -> PyResult<&'py PySet> {
+        let bounds = Aabb::from_points(&[point]);
+        self.query_set(py, &bounds)
+    }
+
+    pub fn get_sprites_near_rect<'py>(
+        &self,
+        py: Python<'py>,
+        rect: (f32, f32, f32, f32),
+    ) -> PyResult<&'py PySet> {
+        let bounds = Aabb {
+            left: rect.0,
+            right: rect.1,
+            bottom: rect.2,
+            top: rect.3,
+        };
+        self.query_set(py, &bounds)
+    }
+
+    #[getter]
+    pub fn count(&self) -> usize {
+        self.grid.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bounds(left: f32, right: f32, bottom: f32, top: f32) -> Aabb {
+        Aabb {
+            left,
+            right,
+            bottom,
+            top,
+        }
+    }
+
+    #[test]
+    fn test_hash() {
+        let grid = SpatialGrid::new(10.0);
+        assert_eq!(grid.hash((0.0, 0.0)), (0, 0));
+        assert_eq!(grid.hash((15.0, 29.9)), (1, 2));
+        assert_eq!(grid.hash((-0.5, -10.0)), (-1, -1));
+    }
+
+    #[test]
+    fn test_insert_and_query() {
+        let mut grid = SpatialGrid::new(10.0);
+        grid.insert(1, &bounds(0.0, 5.0, 0.0, 5.0));
+        grid.insert(2, &bounds(5.0, 25.0, 5.0, 25.0));
+        grid.insert(3, &bounds(100.0, 105.0, 100.0, 105.0));
+        assert_eq!(grid.len(), 3);
+
+        assert_eq!(grid.query(&bounds(1.0, 2.0, 1.0, 2.0)), vec![1, 2]);
+        assert_eq!(grid.query(&bounds(12.0, 30.0, 12.0, 30.0)), vec![2]);
+        assert_eq!(grid.query(&bounds(0.0, 200.0, 0.0, 200.0)), vec![1, 2, 3]);
+        assert!(grid.query(&bounds(50.0, 60.0, 50.0, 60.0)).is_empty());
+        assert!(grid.query(&Aabb::EMPTY).is_empty());
+    }
+
+    #[test]
+    fn test_query_segment() {
+        let mut grid = SpatialGrid::new(10.0);
+        grid.insert(1, &bounds(0.0, 5.0, 0.0, 5.0));
+        grid.insert(2, &bounds(21.0, 25.0, 1.0, 5.0));
+        grid.insert(3, &bounds(41.0, 45.0, 41.0, 45.0));
+        // Inside the bounding box of the segment but not in a cell it crosses
+        grid.insert(4, &bounds(1.0, 5.0, 41.0, 45.0));
+        // Enough buckets elsewhere that the segments are walked cell by cell
+        for key in 10..30 {
+            let x = 1000.0 + key as f32 * 10.0;
+            grid.insert(key, &bounds(x, x + 1.0, 0.0, 1.0));
+        }
+
+        assert_eq!(grid.query_segment((1.0, 1.0), (49.0, 49.0)), vec![1, 3]);
+        assert_eq!(grid.query_segment((1.0, 2.0), (29.0, 2.0)), vec![1, 2]);
+        assert_eq!(grid.query_segment((49.0, 9.0), (21.0, 1.0)), vec![2]);
+        assert!(grid.query_segment((15.0, 15.0), (15.0, 15.0)).is_empty());
+    }
+
+    #[test]
+    fn test_query_long_segment() {
+        let mut grid = SpatialGrid::new(10.0);
+        grid.insert(1, &bounds(0.0, 5.0, 0.0, 5.0));
+        grid.insert(2, &bounds(-5.0, 0.0, 100.0, 105.0));
+        // Far more cells than buckets, so everything is a candidate without a long walk
+        let far = 1.0e10 * std::f32::consts::FRAC_1_SQRT_2;
+        assert_eq!(grid.query_segment((1.0, 1.0), (far, far)), vec![1, 2]);
+        assert_eq!(grid.query_segment((1.0, 1.0), (-far, -far)), vec![1, 2]);
+        assert_eq!(grid.query_segment((1.0, 1.0), (far, -1.0)), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut grid = SpatialGrid::new(10.0);
+        grid.insert(1, &bounds(0.0, 25.0, 0.0, 25.0));
+        assert!(grid.remove(1));
+        assert!(!grid.remove(1));
+        assert!(grid.is_empty());
+        assert!(grid.query(&bounds(0.0, 25.0, 0.0, 25.0)).is_empty());
+    }
+
+    #[test]
+    fn test_update() {
+        let mut grid = SpatialGrid::new(10.0);
+        assert!(!grid.update(1, &bounds(0.0, 1.0, 0.0, 1.0)));
+
+        grid.insert(1, &bounds(0.0, 1.0, 0.0, 1.0));
+        assert!(grid.update(1, &bounds(2.0, 3.0, 2.0, 3.0)));
+        assert_eq!(grid.query(&bounds(0.0, 1.0, 0.0, 1.0)), vec![1]);
+
+        assert!(grid.update(1, &bounds(42.0, 43.0, 42.0, 43.0)));
+        assert!(grid.query(&bounds(0.0, 1.0, 0.0, 1.0)).is_empty());
+        assert_eq!(grid.query(&bounds(40.0, 41.0, 40.0, 41.0)), vec![1]);
+        assert_eq!(grid.len(), 1);
+    }
+}
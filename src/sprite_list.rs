@@ -1,62 +1,138 @@
-use crate::geometry::are_polygons_intersecting_native;
-use crate::hitbox::{HitBox, NativeAdjustedPoints, RotatableHitBox};
+use crate::geometry::{
+    are_bounds_overlapping, get_polygons_time_of_impact_native, get_ray_direction, Aabb, RayHit,
+    TimeOfImpact,
+};
+use crate::hitbox::ExtractedHitBox;
+use crate::spatial_hash::SpatialHash;
+use pyo3::exceptions::PyValueError;
 use pyo3::intern;
 use pyo3::prelude::*;
 
+/// The `method` argument of the collision functions, numbered the same as in arcade.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CollisionMethod {
+    Auto,
+    SpatialHash,
+    Gpu,
+    Simple,
+}
+
+impl CollisionMethod {
+    pub fn from_int(method: i32) -> PyResult<CollisionMethod> {
+        match method {
+            0 => Ok(CollisionMethod::Auto),
+            1 => Ok(CollisionMethod::SpatialHash),
+            2 => Ok(CollisionMethod::Gpu),
+            3 => Ok(CollisionMethod::Simple),
+            _ => Err(PyValueError::new_err(format!(
+                "Unknown collision method {}, expected 0 (auto), 1 (spatial hash), 2 (GPU) or 3 (simple)",
+                method
+            ))),
+        }
+    }
+}
+
+/// Sprites from the list that could collide with something inside `bounds`.
+///
+/// There is no GPU path natively, so every method other than `Simple` uses the
+/// list's spatial hash when it has one and checks the whole list otherwise. A native
+/// spatial hash is queried directly, a Python one through `get_sprites_near_sprite`
+/// when `sprite` is given, since it only knows the sprite's own bounds.
+fn get_sprites_to_check(
+    py: Python<'_>,
+    sprite: Option<&PyAny>,
+    sprite_list: &PyAny,
+    bounds: &Aabb,
+    method: CollisionMethod,
+) -> PyResult<Vec<PyObject>> {
+    if method == CollisionMethod::Simple {
+        return sprite_list.getattr(intern!(py, "sprite_list"))?.extract();
+    }
+    if let Ok(spatial_hash) = sprite_list.getattr(intern!(py, "spatial_hash")) {
+        if let Ok(spatial_hash) = spatial_hash.extract::<PyRef<SpatialHash>>() {
+            return Ok(spatial_hash.query_native(py, bounds));
+        }
+        if let (Some(sprite), false) = (sprite, spatial_hash.is_none()) {
+            return spatial_hash
+                .call_method1(intern!(py, "get_sprites_near_sprite"), (sprite,))?
+                .iter()?
+                .map(|sprite2| sprite2.map(|s| s.to_object(py)))
+                .collect();
+        }
+    }
+    sprite_list.getattr(intern!(py, "sprite_list"))?.extract()
+}
+
+/// Full collision test between two hit boxes, rejecting far apart pairs before
+/// running the exact shape test.
+fn are_hit_boxes_colliding(hitbox_a: &mut ExtractedHitBox, hitbox_b: &mut ExtractedHitBox) -> bool {
+    let native_a = hitbox_a.native();
+    let bounds_a = native_a.get_bounds_native();
+    let circle_a = native_a.get_bounding_circle_native();
+    let native_b = hitbox_b.native();
+    let bounds_b = native_b.get_bounds_native();
+    let circle_b = native_b.get_bounding_circle_native();
+    if !are_bounds_overlapping(&bounds_a, circle_a, &bounds_b, circle_b) {
+        return false;
+    }
+    hitbox_a.shape().intersects(&hitbox_b.shape())
+}
+
+/// Add the sprites colliding with the main sprite to `colliding`, skipping the main
+/// sprite itself.
+fn collect_colliding_sprites(
+    py: Python<'_>,
+    sprite: &PyAny,
+    main_hitbox_object: &PyAny,
+    main_hitbox: &mut ExtractedHitBox,
+    sprites_to_check: Vec<PyObject>,
+    colliding: &mut Vec<PyObject>,
+) -> PyResult<()> {
+    for sprite2 in sprites_to_check {
+        let other = sprite2.as_ref(py);
+        if other.is(sprite) {
+            continue;
+        }
+        let other_hitbox_object = other.getattr(intern!(py, "_hit_box"))?;
+        // A hit box object shared with the main sprite is already borrowed by it, and
+        // overlaps it exactly anyway
+        if other_hitbox_object.is(main_hitbox_object)
+            || are_hit_boxes_colliding(
+                main_hitbox,
+                &mut ExtractedHitBox::from_hit_box(py, other_hitbox_object)?,
+            )
+        {
+            colliding.push(sprite2);
+        }
+    }
+    Ok(())
+}
+
 #[pyfunction]
 pub fn check_for_collision_with_list(
     py: Python<'_>,
     sprite: &PyAny, //
     sprite_list: &PyAny,
     method: Option<i32>,
-) -> Vec<PyObject> {
-    let _final_method = method.unwrap_or(3);
+) -> PyResult<Vec<PyObject>> {
+    let final_method = CollisionMethod::from_int(method.unwrap_or(0))?;
     let mut final_sprites: Vec<PyObject> = Vec::new();
 
-    let main_points: &Vec<(f32, f32)>;
-    let mut hitbox1: HitBox;
-    let mut hitbox2: RotatableHitBox;
-    let hitbox_py_object: &PyAny = sprite.getattr(intern!(py, "_hit_box")).unwrap();
-
-    if hitbox_py_object.is_instance_of::<HitBox>() {
-        hitbox1 = hitbox_py_object.extract::<HitBox>().unwrap();
-        main_points = hitbox1.get_adjusted_points_native();
-    } else if hitbox_py_object.is_instance_of::<RotatableHitBox>() {
-        hitbox2 = hitbox_py_object.extract::<RotatableHitBox>().unwrap();
-        main_points = hitbox2.get_adjusted_points_native();
-    } else {
-        panic!("Unknown Hitbox Type")
-    }
-
-    let sprite_list_list = sprite_list.getattr(intern!(py, "sprite_list")).unwrap();
-    let sprites_to_check: Vec<PyObject> = sprite_list_list.extract().unwrap();
-
-    for sprite2 in sprites_to_check.iter() {
-        let other_sprite: &PyAny = sprite2.as_ref(py);
-
-        let other_points: &Vec<(f32, f32)>;
-        let mut other_hitbox1: HitBox;
-        let mut other_hitbox2: RotatableHitBox;
-        let other_hitbox_py_object: &PyAny = other_sprite.getattr(intern!(py, "_hit_box")).unwrap();
-
-        if other_hitbox_py_object.is_instance_of::<HitBox>() {
-            other_hitbox1 = other_hitbox_py_object.extract::<HitBox>().unwrap();
-            other_points = other_hitbox1.get_adjusted_points_native();
-        } else if other_hitbox_py_object.is_instance_of::<RotatableHitBox>() {
-            other_hitbox2 = other_hitbox_py_object.extract::<RotatableHitBox>().unwrap();
-            other_points = other_hitbox2.get_adjusted_points_native();
-        } else {
-            panic!("Unknown Hitbox Type")
-        }
-
-        let check_2 = are_polygons_intersecting_native(main_points, other_points);
-
-        if check_2 {
-            final_sprites.push(sprite2.to_object(py));
-        }
-    }
+    let main_hitbox_object = sprite.getattr(intern!(py, "_hit_box"))?;
+    let mut main_hitbox = ExtractedHitBox::from_hit_box(py, main_hitbox_object)?;
+    let main_bounds = main_hitbox.native().get_bounds_native();
+    let sprites_to_check =
+        get_sprites_to_check(py, Some(sprite), sprite_list, &main_bounds, final_method)?;
+    collect_colliding_sprites(
+        py,
+        sprite,
+        main_hitbox_object,
+        &mut main_hitbox,
+        sprites_to_check,
+        &mut final_sprites,
+    )?;
 
-    final_sprites
+    Ok(final_sprites)
 }
 
 #[pyfunction]
@@ -64,54 +140,320 @@ pub fn check_for_collision_with_lists(
     py: Python<'_>,
     sprite: &PyAny, //
     sprite_lists: Vec<&PyAny>,
-) -> Vec<PyObject> {
+    method: Option<i32>,
+) -> PyResult<Vec<PyObject>> {
+    let final_method = CollisionMethod::from_int(method.unwrap_or(1))?;
     let mut final_sprites: Vec<PyObject> = Vec::new();
 
-    let main_points: &Vec<(f32, f32)>;
-    let mut hitbox1: HitBox;
-    let mut hitbox2: RotatableHitBox;
-    let hitbox_py_object: &PyAny = sprite.getattr(intern!(py, "_hit_box")).unwrap();
+    let main_hitbox_object = sprite.getattr(intern!(py, "_hit_box"))?;
+    let mut main_hitbox = ExtractedHitBox::from_hit_box(py, main_hitbox_object)?;
+    let main_bounds = main_hitbox.native().get_bounds_native();
 
-    if hitbox_py_object.is_instance_of::<HitBox>() {
-        hitbox1 = hitbox_py_object.extract::<HitBox>().unwrap();
-        main_points = hitbox1.get_adjusted_points_native();
-    } else if hitbox_py_object.is_instance_of::<RotatableHitBox>() {
-        hitbox2 = hitbox_py_object.extract::<RotatableHitBox>().unwrap();
-        main_points = hitbox2.get_adjusted_points_native();
-    } else {
-        panic!("Unknown Hitbox Type")
+    for sprite_list in sprite_lists.iter() {
+        let sprites_to_check =
+            get_sprites_to_check(py, Some(sprite), sprite_list, &main_bounds, final_method)?;
+        collect_colliding_sprites(
+            py,
+            sprite,
+            main_hitbox_object,
+            &mut main_hitbox,
+            sprites_to_check,
+            &mut final_sprites,
+        )?;
     }
 
-    for sprite_list in sprite_lists.iter() {
-        let sprite_list_list = sprite_list.getattr(intern!(py, "sprite_list")).unwrap();
-        let sprites_to_check: Vec<PyObject> = sprite_list_list.extract().unwrap();
-
-        for sprite2 in sprites_to_check.iter() {
-            let other_sprite: &PyAny = sprite2.as_ref(py);
-
-            let other_points: &Vec<(f32, f32)>;
-            let mut other_hitbox1: HitBox;
-            let mut other_hitbox2: RotatableHitBox;
-            let other_hitbox_py_object: &PyAny =
-                other_sprite.getattr(intern!(py, "_hit_box")).unwrap();
-
-            if other_hitbox_py_object.is_instance_of::<HitBox>() {
-                other_hitbox1 = other_hitbox_py_object.extract::<HitBox>().unwrap();
-                other_points = other_hitbox1.get_adjusted_points_native();
-            } else if other_hitbox_py_object.is_instance_of::<RotatableHitBox>() {
-                other_hitbox2 = other_hitbox_py_object.extract::<RotatableHitBox>().unwrap();
-                other_points = other_hitbox2.get_adjusted_points_native();
-            } else {
-                panic!("Unknown Hitbox Type")
+    Ok(final_sprites)
+}
+
+/// The earliest impact between any polygon of `polygons_a` and any of `polygons_b`.
+fn get_time_of_impact_native(
+    polygons_a: &[&[(f32, f32)]],
+    displacement_a: (f32, f32),
+    polygons_b: &[&[(f32, f32)]],
+    displacement_b: (f32, f32),
+) -> Option<TimeOfImpact> {
+    let mut first: Option<TimeOfImpact> = None;
+    for poly_a in polygons_a {
+        for poly_b in polygons_b {
+            let impact =
+                get_polygons_time_of_impact_native(poly_a, displacement_a, poly_b, displacement_b);
+            if let Some(impact) = impact {
+                if first.is_none_or(|first| impact.time < first.time) {
+                    first = Some(impact);
+                }
             }
+        }
+    }
+    first
+}
+
+/// When `hit_box_a` moving by `displacement_a` first touches `hit_box_b` moving by
+/// `displacement_b`, or `None` if they never do during the move.
+///
+/// Each polygon, or each part of a compound hit box, is treated as convex, and circles
+/// and capsules are swept as their polygon outlines.
+#[pyfunction]
+pub fn get_time_of_impact(
+    py: Python<'_>,
+    hit_box_a: &PyAny,
+    displacement_a: (f32, f32),
+    hit_box_b: &PyAny,
+    displacement_b: Option<(f32, f32)>,
+) -> PyResult<Option<TimeOfImpact>> {
+    let displacement_b = displacement_b.unwrap_or((0.0, 0.0));
+    let mut hitbox_a = ExtractedHitBox::from_hit_box(py, hit_box_a)?;
+    let polygons_a = hitbox_a.polygons();
+    if hit_box_a.is(hit_box_b) {
+        // Already borrowed, and a hit box always overlaps itself
+        return Ok(get_time_of_impact_native(
+            &polygons_a,
+            displacement_a,
+            &polygons_a,
+            displacement_b,
+        ));
+    }
+    let mut hitbox_b = ExtractedHitBox::from_hit_box(py, hit_box_b)?;
+    Ok(get_time_of_impact_native(
+        &polygons_a,
+        displacement_a,
+        &hitbox_b.polygons(),
+        displacement_b,
+    ))
+}
+
+/// Sweep a sprite's hit box by `displacement` through a sprite list and return the
+/// first sprite it would hit along with the impact, or `None` if the path is clear.
+///
+/// The sprites in the list are treated as stationary and the sprite itself is skipped.
+/// `method` picks the sprites to test the same way as `check_for_collision_with_list`,
+/// but a spatial hash implemented in Python is not used since it can not be queried
+/// with the swept area.
+#[pyfunction]
+pub fn cast_sprite_against_list(
+    py: Python<'_>,
+    sprite: &PyAny,
+    displacement: (f32, f32),
+    sprite_list: &PyAny,
+    method: Option<i32>,
+) -> PyResult<Option<(PyObject, TimeOfImpact)>> {
+    let final_method = CollisionMethod::from_int(method.unwrap_or(0))?;
 
-            let check_2 = are_polygons_intersecting_native(main_points, other_points);
+    let main_hitbox_object = sprite.getattr(intern!(py, "_hit_box"))?;
+    let mut main_hitbox = ExtractedHitBox::from_hit_box(py, main_hitbox_object)?;
+    let swept_bounds = main_hitbox.native().get_bounds_native().swept(displacement);
+    let main_polygons = main_hitbox.polygons();
+    let sprites_to_check =
+        get_sprites_to_check(py, None, sprite_list, &swept_bounds, final_method)?;
 
-            if check_2 {
-                final_sprites.push(sprite2.to_object(py));
+    let mut first: Option<(PyObject, TimeOfImpact)> = None;
+    for sprite2 in sprites_to_check {
+        let other = sprite2.as_ref(py);
+        if other.is(sprite) {
+            continue;
+        }
+        let other_hitbox_object = other.getattr(intern!(py, "_hit_box"))?;
+        let impact = if other_hitbox_object.is(main_hitbox_object) {
+            // Already borrowed by the main sprite, and overlapping it exactly
+            get_time_of_impact_native(&main_polygons, displacement, &main_polygons, (0.0, 0.0))
+        } else {
+            let mut other_hitbox = ExtractedHitBox::from_hit_box(py, other_hitbox_object)?;
+            if !other_hitbox
+                .native()
+                .get_bounds_native()
+                .intersects(&swept_bounds)
+            {
+                continue;
+            }
+            get_time_of_impact_native(
+                &main_polygons,
+                displacement,
+                &other_hitbox.polygons(),
+                (0.0, 0.0),
+            )
+        };
+        if let Some(impact) = impact {
+            if first
+                .as_ref()
+                .is_none_or(|(_, first)| impact.time < first.time)
+            {
+                first = Some((sprite2, impact));
             }
         }
     }
+    Ok(first)
+}
+
+/// Sprites from the list that a ray with unit `direction` could hit within `max_distance`.
+fn get_sprites_along_ray(
+    py: Python<'_>,
+    origin: (f32, f32),
+    direction: (f32, f32),
+    sprite_list: &PyAny,
+    max_distance: f32,
+    method: CollisionMethod,
+) -> PyResult<Vec<PyObject>> {
+    // A ray without an end can not be walked through a spatial hash
+    if method == CollisionMethod::Simple || !max_distance.is_finite() {
+        return get_sprites_to_check(py, None, sprite_list, &Aabb::EMPTY, CollisionMethod::Simple);
+    }
+    let end = (
+        origin.0 + direction.0 * max_distance,
+        origin.1 + direction.1 * max_distance,
+    );
+    if let Ok(spatial_hash) = sprite_list.getattr(intern!(py, "spatial_hash")) {
+        if let Ok(spatial_hash) = spatial_hash.extract::<PyRef<SpatialHash>>() {
+            return Ok(spatial_hash.query_segment_native(py, origin, end));
+        }
+    }
+    get_sprites_to_check(py, None, sprite_list, &Aabb::EMPTY, CollisionMethod::Simple)
+}
+
+/// Where a ray with unit `direction` first hits a sprite's hit box within `max_distance`.
+fn cast_ray_against_sprite(
+    py: Python<'_>,
+    origin: (f32, f32),
+    direction: (f32, f32),
+    sprite: &PyAny,
+    max_distance: f32,
+) -> PyResult<Option<RayHit>> {
+    let mut hitbox = ExtractedHitBox::from_hit_box(py, sprite.getattr(intern!(py, "_hit_box"))?)?;
+    if !hitbox
+        .native()
+        .get_bounds_native()
+        .is_hit_by_ray(origin, direction, max_distance)
+    {
+        return Ok(None);
+    }
+    Ok(hitbox.shape().cast_ray(origin, direction, max_distance))
+}
+
+/// Every sprite in the list hit by a ray with unit `direction`, nearest first. Only the
+/// nearest hit is kept when `nearest_only` is set.
+fn collect_ray_hits(
+    py: Python<'_>,
+    origin: (f32, f32),
+    direction: (f32, f32),
+    sprite_list: &PyAny,
+    max_distance: f32,
+    method: CollisionMethod,
+    nearest_only: bool,
+) -> PyResult<Vec<(PyObject, RayHit)>> {
+    let sprites_to_check =
+        get_sprites_along_ray(py, origin, direction, sprite_list, max_distance, method)?;
+    let mut max_distance = max_distance;
+    let mut hits: Vec<(PyObject, RayHit)> = Vec::new();
+    for sprite in sprites_to_check {
+        let hit = cast_ray_against_sprite(py, origin, direction, sprite.as_ref(py), max_distance)?;
+        let Some(hit) = hit else {
+            continue;
+        };
+        if nearest_only {
+            max_distance = hit.distance;
+            hits.clear();
+        }
+        hits.push((sprite, hit));
+    }
+    hits.sort_by(|(_, a), (_, b)| a.distance.total_cmp(&b.distance));
+    Ok(hits)
+}
 
-    final_sprites
+/// The first sprite in the list hit by a ray from `origin` heading in `direction`,
+/// along with where it was hit, or `None` if nothing is hit within `max_distance`.
+///
+/// `max_distance` defaults to no limit. `method` picks the sprites to test the same way
+/// as `check_for_collision_with_list`, but only for rays with a maximum distance, and a
+/// spatial hash implemented in Python is not used.
+#[pyfunction]
+pub fn cast_ray_against_list(
+    py: Python<'_>,
+    origin: (f32, f32),
+    direction: (f32, f32),
+    sprite_list: &PyAny,
+    max_distance: Option<f32>,
+    method: Option<i32>,
+) -> PyResult<Option<(PyObject, RayHit)>> {
+    let hits = collect_ray_hits(
+        py,
+        origin,
+        get_ray_direction(direction)?,
+        sprite_list,
+        max_distance.unwrap_or(f32::INFINITY),
+        CollisionMethod::from_int(method.unwrap_or(0))?,
+        true,
+    )?;
+    Ok(hits.into_iter().next())
+}
+
+/// Every sprite in the list hit by a ray, nearest first, see `cast_ray_against_list`.
+#[pyfunction]
+pub fn cast_ray_against_list_all(
+    py: Python<'_>,
+    origin: (f32, f32),
+    direction: (f32, f32),
+    sprite_list: &PyAny,
+    max_distance: Option<f32>,
+    method: Option<i32>,
+) -> PyResult<Vec<(PyObject, RayHit)>> {
+    collect_ray_hits(
+        py,
+        origin,
+        get_ray_direction(direction)?,
+        sprite_list,
+        max_distance.unwrap_or(f32::INFINITY),
+        CollisionMethod::from_int(method.unwrap_or(0))?,
+        false,
+    )
+}
+
+/// Whether the straight line from `observer` to `target` is clear of every wall's hit
+/// box, testing the exact segment instead of stepping along it like arcade does.
+///
+/// Targets further away than `max_distance` are never visible. `check_resolution` has
+/// no effect, since the whole segment is tested: it is only accepted, and still has to
+/// be positive, so calls written for arcade's version keep working.
+#[pyfunction]
+pub fn has_line_of_sight(
+    py: Python<'_>,
+    observer: (f32, f32),
+    target: (f32, f32),
+    walls: &PyAny,
+    max_distance: Option<f32>,
+    check_resolution: Option<i32>,
+) -> PyResult<bool> {
+    let max_distance = max_distance.unwrap_or(f32::INFINITY);
+    if max_distance <= 0.0 {
+        return Err(PyValueError::new_err(
+            "max_distance must be greater than zero",
+        ));
+    }
+    if check_resolution.unwrap_or(2) <= 0 {
+        return Err(PyValueError::new_err(
+            "check_resolution must be greater than zero",
+        ));
+    }
+
+    let offset = (target.0 - observer.0, target.1 - observer.1);
+    let distance = (offset.0 * offset.0 + offset.1 * offset.1).sqrt();
+    if distance == 0.0 {
+        return Ok(true);
+    }
+    if distance > max_distance {
+        return Ok(false);
+    }
+    let direction = (offset.0 / distance, offset.1 / distance);
+
+    let sprites_to_check = get_sprites_along_ray(
+        py,
+        observer,
+        direction,
+        walls,
+        distance,
+        CollisionMethod::Auto,
+    )?;
+    for wall in sprites_to_check {
+        if cast_ray_against_sprite(py, observer, direction, wall.as_ref(py), distance)?.is_some() {
+            return Ok(false);
+        }
+    }
+    Ok(true)
 }